@@ -1,23 +1,29 @@
-use crate::db::{Config, DbState, Feature, FeatureUpdate, GraphFeatureSync, UpdateSource};
+use crate::db::{
+    Config, DbState, Feature, FeatureChange, FeatureUpdate, FieldLock, SearchQuery, SearchResults, UpdateAuthor,
+    UpdateOutcome, UpdateSource,
+};
 use crate::graph_db;
 use crate::plugin_manager::PluginManager;
+use crate::retention::{self, CompactionReport};
+use crate::server::{ServerState, ServerStatus};
+use crate::store::DataStore;
 use crate::GraphDbState;
 use serde::Serialize;
 use tauri::State;
 
 // =============================================================================
-// FEATURE COMMANDS (still using SQLite for backwards compatibility)
+// FEATURE COMMANDS (Memgraph, falling back to the SQLite cache)
 // =============================================================================
 
 #[tauri::command]
 pub async fn get_features(
     graph_db: State<'_, GraphDbState>,
+    db: State<'_, DbState>,
     project_dir: Option<String>,
 ) -> Result<Vec<graph_db::Feature>, String> {
     match project_dir {
-        Some(dir) => graph_db
-            .0
-            .get_features_for_project(&dir)
+        Some(dir) => DataStore::new(&graph_db.0, &db.0)
+            .get_features(&dir)
             .await
             .map_err(|e| e.to_string()),
         None => Ok(vec![]), // No global feature list without project
@@ -25,17 +31,17 @@ pub async fn get_features(
 }
 
 // =============================================================================
-// EVENT COMMANDS (using Memgraph - single source of truth)
+// EVENT COMMANDS (Memgraph, falling back to the SQLite cache)
 // =============================================================================
 
 #[tauri::command]
 pub async fn get_events(
     graph_db: State<'_, GraphDbState>,
+    db: State<'_, DbState>,
     limit: Option<i64>,
 ) -> Result<Vec<graph_db::Event>, String> {
-    graph_db
-        .0
-        .get_all_recent_events(limit.unwrap_or(50))
+    DataStore::new(&graph_db.0, &db.0)
+        .get_events(limit.unwrap_or(50))
         .await
         .map_err(|e| e.to_string())
 }
@@ -43,44 +49,44 @@ pub async fn get_events(
 #[tauri::command]
 pub async fn get_feature_events(
     graph_db: State<'_, GraphDbState>,
+    db: State<'_, DbState>,
     feature_id: String,
     limit: Option<i64>,
 ) -> Result<Vec<graph_db::Event>, String> {
-    graph_db
-        .0
-        .get_events_by_feature(&feature_id, limit.unwrap_or(100))
+    DataStore::new(&graph_db.0, &db.0)
+        .get_feature_events(&feature_id, limit.unwrap_or(100))
         .await
         .map_err(|e| e.to_string())
 }
 
 // =============================================================================
-// SESSION COMMANDS (using Memgraph)
+// SESSION COMMANDS (Memgraph, falling back to the SQLite cache)
 // =============================================================================
 
 #[tauri::command]
 pub async fn get_sessions(
     graph_db: State<'_, GraphDbState>,
+    db: State<'_, DbState>,
 ) -> Result<Vec<graph_db::Session>, String> {
-    graph_db
-        .0
-        .get_all_sessions(100)
+    DataStore::new(&graph_db.0, &db.0)
+        .get_sessions(100)
         .await
         .map_err(|e| e.to_string())
 }
 
 // =============================================================================
-// STATS COMMANDS (using Memgraph)
+// STATS COMMANDS (Memgraph, falling back to the SQLite cache)
 // =============================================================================
 
 #[tauri::command]
 pub async fn get_stats(
     graph_db: State<'_, GraphDbState>,
+    db: State<'_, DbState>,
     project_path: Option<String>,
 ) -> Result<graph_db::ProjectStats, String> {
     match project_path {
-        Some(path) => graph_db
-            .0
-            .get_project_stats(&path)
+        Some(path) => DataStore::new(&graph_db.0, &db.0)
+            .get_stats(&path)
             .await
             .map_err(|e| e.to_string()),
         None => Ok(graph_db::ProjectStats {
@@ -94,19 +100,18 @@ pub async fn get_stats(
 }
 
 // =============================================================================
-// PROJECT COMMANDS (using Memgraph)
+// PROJECT COMMANDS (Memgraph, falling back to the SQLite cache)
 // =============================================================================
 
 #[tauri::command]
 pub async fn get_projects(
     graph_db: State<'_, GraphDbState>,
+    db: State<'_, DbState>,
 ) -> Result<Vec<String>, String> {
-    let projects = graph_db
-        .0
+    DataStore::new(&graph_db.0, &db.0)
         .get_projects()
         .await
-        .map_err(|e| e.to_string())?;
-    Ok(projects.into_iter().map(|p| p.path).collect())
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -172,24 +177,37 @@ pub async fn watch_project(
     db: State<'_, DbState>,
     project_dir: String,
 ) -> Result<(), String> {
-    let mut config = db.0.get_config().map_err(|e| e.to_string())?;
-
-    if !config.watched_projects.contains(&project_dir) {
-        config.watched_projects.push(project_dir);
-        db.0.save_config(&config).map_err(|e| e.to_string())?;
-    }
+    db.0.run(move |db| db.add_watched_project(&project_dir))
+        .await
+        .map_err(|e| e.to_string())?;
 
     Ok(())
 }
 
 #[tauri::command]
 pub async fn get_config(db: State<'_, DbState>) -> Result<Config, String> {
-    db.0.get_config().map_err(|e| e.to_string())
+    db.0.run(|db| db.get_config())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_config(
+    db: State<'_, DbState>,
+    server: State<'_, ServerState>,
+    config: Config,
+) -> Result<(), String> {
+    let port = config.sync_server_port;
+    db.0.run(move |db| db.save_config(&config))
+        .await
+        .map_err(|e| e.to_string())?;
+    server.0.set_port(port);
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn save_config(db: State<'_, DbState>, config: Config) -> Result<(), String> {
-    db.0.save_config(&config).map_err(|e| e.to_string())
+pub async fn get_server_status(server: State<'_, ServerState>) -> Result<ServerStatus, String> {
+    Ok(server.0.status())
 }
 
 // Plugin management commands
@@ -225,11 +243,13 @@ pub async fn install_integration(project_dir: String) -> Result<String, String>
     Ok("Integration installed successfully".to_string())
 }
 
-/// Update a feature with source-aware override logic
+/// Update a feature with source-aware override logic and push the change to
+/// the graph
 /// source: "human" for UI interactions, "agent" for programmatic updates
 #[tauri::command]
 pub async fn update_feature(
     db: State<'_, DbState>,
+    graph_db: State<'_, GraphDbState>,
     feature_id: String,
     update: FeatureUpdate,
     source: String,
@@ -240,7 +260,54 @@ pub async fn update_feature(
         _ => return Err("Invalid source: must be 'human' or 'agent'".to_string()),
     };
 
-    db.0.update_feature(&feature_id, update, update_source)
+    let outcome = DataStore::new(&graph_db.0, &db.0)
+        .update_feature(&feature_id, update, update_source, UpdateAuthor::default())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    match outcome {
+        UpdateOutcome::Applied(_) => Ok(true),
+        UpdateOutcome::Locked(fields) => Err(format!("Fields locked by a human edit: {}", fields.join(", "))),
+        UpdateOutcome::NotFound => Ok(false),
+    }
+}
+
+/// Get the feature's fields currently locked by a human edit
+#[tauri::command]
+pub async fn get_feature_locks(
+    db: State<'_, DbState>,
+    feature_id: String,
+) -> Result<Vec<FieldLock>, String> {
+    db.0.run(move |db| db.get_feature_locks(&feature_id))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Release human locks on a feature so agents can update it again.
+/// fields: the fields to release, or all of them when omitted
+#[tauri::command]
+pub async fn release_override(
+    graph_db: State<'_, GraphDbState>,
+    db: State<'_, DbState>,
+    feature_id: String,
+    fields: Option<Vec<String>>,
+) -> Result<usize, String> {
+    DataStore::new(&graph_db.0, &db.0)
+        .release_override(&feature_id, fields)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get a feature's recorded field changes, newest first
+#[tauri::command]
+pub async fn get_feature_history(
+    db: State<'_, DbState>,
+    feature_id: String,
+    limit: Option<i64>,
+) -> Result<Vec<FeatureChange>, String> {
+    let limit = limit.unwrap_or(100);
+    db.0.run(move |db| db.get_feature_history(&feature_id, limit))
+        .await
         .map_err(|e| e.to_string())
 }
 
@@ -250,7 +317,35 @@ pub async fn get_feature(
     db: State<'_, DbState>,
     feature_id: String,
 ) -> Result<Option<Feature>, String> {
-    db.0.get_feature(&feature_id)
+    db.0.run(move |db| db.get_feature(&feature_id))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Full-text search over event payloads and features, each ranked best match
+/// first in its own list. `from`/`to` accept RFC 3339 timestamps or
+/// YYYY-MM-DD dates.
+#[tauri::command]
+pub async fn search(
+    db: State<'_, DbState>,
+    query: String,
+    project_dir: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<i64>,
+) -> Result<SearchResults, String> {
+    let query = SearchQuery::parse(&query, project_dir, from.as_deref(), to.as_deref(), limit)?;
+
+    db.0.run(move |db| db.search(&query))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Apply the retention policy now and reclaim the freed disk space
+#[tauri::command]
+pub async fn compact_database(db: State<'_, DbState>) -> Result<CompactionReport, String> {
+    db.0.run(|db| retention::compact_database(db, true))
+        .await
         .map_err(|e| e.to_string())
 }
 
@@ -262,6 +357,8 @@ pub async fn get_feature(
 pub struct GraphDbStatus {
     pub connected: bool,
     pub uri: String,
+    pub database: String,
+    pub dialect: String,
 }
 
 /// Check if graph database is connected
@@ -270,10 +367,12 @@ pub async fn get_graph_db_status(
     graph_db: State<'_, GraphDbState>,
 ) -> Result<GraphDbStatus, String> {
     let connected = graph_db.0.is_connected().await;
+    let config = graph_db.0.config();
     Ok(GraphDbStatus {
         connected,
-        uri: std::env::var("IJOKA_GRAPH_URI")
-            .unwrap_or_else(|_| "bolt://localhost:7687".to_string()),
+        uri: config.uri.clone(),
+        database: config.database.clone(),
+        dialect: format!("{:?}", config.dialect).to_lowercase(),
     })
 }
 
@@ -328,7 +427,7 @@ pub async fn get_graph_project_stats(
         .map_err(|e| e.to_string())
 }
 
-/// Sync projects from graph to SQLite cache
+/// Fully resync features from graph to SQLite cache
 #[tauri::command]
 pub async fn sync_graph_to_cache(
     graph_db: State<'_, GraphDbState>,
@@ -338,49 +437,12 @@ pub async fn sync_graph_to_cache(
         return Err("Graph database not connected".to_string());
     }
 
-    // Get all projects from graph
-    let projects = graph_db
-        .0
-        .get_projects()
+    // Resetting the watermark makes the incremental sync consider every feature
+    db.0.run(|db| db.set_graph_sync_watermark(0))
         .await
         .map_err(|e| e.to_string())?;
 
-    let mut synced_features = 0;
-
-    for project in &projects {
-        // Get features for each project
-        let features = graph_db
-            .0
-            .get_features_for_project(&project.path)
-            .await
-            .map_err(|e| e.to_string())?;
-
-        // Sync each feature to SQLite cache
-        for feature in features {
-            // Convert graph_db::Feature to GraphFeatureSync for SQLite upsert
-            if let Some(id) = &feature.id {
-                let sync_feature = GraphFeatureSync {
-                    id: id.clone(),
-                    project_dir: project.path.clone(),
-                    description: feature.description.clone(),
-                    category: feature.category.clone(),
-                    status: feature.status.clone(),
-                    steps: feature.steps.clone().unwrap_or_default(),
-                };
-
-                // Attempt to insert/update the feature
-                if let Err(e) = db.0.sync_feature_from_graph(&sync_feature) {
-                    tracing::warn!("Failed to sync feature {}: {}", id, e);
-                } else {
-                    synced_features += 1;
-                }
-            }
-        }
-    }
+    let changed_ids = crate::sync::sync_graph_to_sqlite(&graph_db.0, &db.0).await?;
 
-    Ok(format!(
-        "Synced {} features from {} projects",
-        synced_features,
-        projects.len()
-    ))
+    Ok(format!("Synced {} changed features", changed_ids.len()))
 }
@@ -1,96 +1,66 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
-mod db;
-mod graph_db;
 mod plugin_manager;
-mod server;
-mod watcher;
 mod workflow_service;
 
+// Core modules live in ijoka-core; re-export them so `crate::db` etc. resolve
+use ijoka_core::{auth, daemon, db, graph_db, mcp, retention, server, store, sync};
+
+use ijoka_core::{Daemon, EventSink};
+use serde_json::Value;
 use std::sync::Arc;
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Emitter, Manager, WindowEvent,
+    AppHandle, Emitter, Manager, WindowEvent,
 };
-use tokio::sync::broadcast;
+use tauri_plugin_notification::NotificationExt;
 use tracing_subscriber;
 
 /// State wrapper for the graph database connection
 pub struct GraphDbState(pub Arc<graph_db::GraphDb>);
 
+/// Forwards daemon events to the webview and notifications to the OS
+struct TauriSink(AppHandle);
+
+impl EventSink for TauriSink {
+    fn emit(&self, event: &str, payload: Value) {
+        let _ = self.0.emit(event, payload);
+    }
+
+    fn notify(&self, title: &str, body: &str) {
+        let _ = self.0.notification().builder().title(title).body(body).show();
+    }
+}
+
 fn main() {
+    // `ijoka mcp` serves MCP over stdio for agents instead of starting the app
+    if std::env::args().nth(1).as_deref() == Some("mcp") {
+        // stdout carries the protocol, so log to stderr
+        tracing_subscriber::fmt().with_writer(std::io::stderr).init();
+        if let Err(e) = mcp::run_stdio() {
+            tracing::error!("MCP server error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Initialize logging
     tracing_subscriber::fmt::init();
 
-    // Event channel for real-time updates
-    let (event_tx, _) = broadcast::channel::<db::AgentEvent>(100);
-    let event_tx = Arc::new(event_tx);
-
     tauri::Builder::default()
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
-        .setup(move |app| {
-            let handle = app.handle().clone();
-            let _event_tx_clone = Arc::clone(&event_tx);
-
-            // Migrate from legacy AgentKanban location if needed
-            if let Err(e) = db::migrate_from_legacy() {
-                tracing::warn!("Database migration warning: {}", e);
-            }
-
-            // Initialize database at ~/.ijoka/ijoka.db
-            // This path is shared with Claude Code hooks for single source of truth
-            let db_path = db::get_standard_db_path();
-
-            // Ensure parent directory exists
-            if let Some(parent) = db_path.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
-
-            tracing::info!("Using shared database at {:?}", db_path);
-            let database = db::Database::new(&db_path)?;
-            app.manage(db::DbState(Arc::new(database)));
-
-            // Initialize graph database (Memgraph) connection
-            // This is optional - the app works without it, using SQLite as fallback
-            let graph_db = Arc::new(graph_db::GraphDb::new());
-            app.manage(GraphDbState(Arc::clone(&graph_db)));
-
-            // Try to connect to Memgraph in background (non-blocking)
-            let graph_handle = app.handle().clone();
-            let sync_graph_db = Arc::clone(&graph_db);
-            let connect_graph_db = Arc::clone(&graph_db);
-            tauri::async_runtime::spawn(async move {
-                match connect_graph_db.connect().await {
-                    Ok(_) => {
-                        tracing::info!("Connected to Memgraph graph database");
-                        let _ = graph_handle.emit("graph-db-connected", true);
-
-                        // Sync graph data to SQLite cache immediately after connection
-                        if let Some(db_state) = graph_handle.try_state::<db::DbState>() {
-                            match sync_graph_to_sqlite(&sync_graph_db, &db_state.0).await {
-                                Ok(count) => {
-                                    tracing::info!("Initial graph->SQLite sync: {} features synced", count);
-                                    let _ = graph_handle.emit("cache-synced", count);
-                                }
-                                Err(e) => {
-                                    tracing::warn!("Initial sync failed: {}", e);
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            "Graph database not available: {}. Using SQLite-only mode.",
-                            e
-                        );
-                        let _ = graph_handle.emit("graph-db-connected", false);
-                    }
-                }
-            });
+        .setup(|app| {
+            // Storage, ingestion server, graph sync, file watcher and session
+            // cleanup all run in the embedded daemon, unless ijokad is
+            // already running them
+            let daemon = Daemon::open(Arc::new(TauriSink(app.handle().clone())))?;
+            app.manage(db::DbState(Arc::clone(&daemon.db)));
+            app.manage(GraphDbState(Arc::clone(&daemon.graph)));
+            app.manage(server::ServerState(Arc::clone(&daemon.server)));
 
             // Setup Claude Code plugin
             let plugin_path = plugin_manager::PluginManager::default_plugin_path();
@@ -104,96 +74,16 @@ fn main() {
                 }
             }
 
-            // Start file watcher in background thread
-            let watcher_handle = handle.clone();
-            let watcher_tx = Arc::clone(&event_tx);
-            std::thread::spawn(move || {
-                if let Err(e) = watcher::start_watching(watcher_handle, watcher_tx) {
-                    tracing::error!("File watcher error: {}", e);
-                }
-            });
-
-            // Start HTTP server for hook events
-            let http_handle = handle.clone();
-            let http_tx = Arc::clone(&event_tx);
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = server::start_server(http_handle, http_tx, 4000).await {
-                    tracing::error!("HTTP server error: {}", e);
-                }
-            });
-
-            // Forward events to frontend via Tauri events
-            let event_handle = handle.clone();
-            let mut event_rx = event_tx.subscribe();
-            tauri::async_runtime::spawn(async move {
-                while let Ok(event) = event_rx.recv().await {
-                    let _ = event_handle.emit("agent-event", &event);
-                }
-            });
-
-            // Cleanup stale sessions on startup and periodically (every 2 minutes)
-            let cleanup_handle = handle.clone();
-            tauri::async_runtime::spawn(async move {
-                // Run cleanup immediately on startup
-                if let Some(db_state) = cleanup_handle.try_state::<db::DbState>() {
-                    if let Ok(count) = db_state.0.cleanup_stale_sessions(15) {
-                        if count > 0 {
-                            tracing::info!("Startup cleanup: marked {} stale sessions as ended", count);
-                        }
-                    }
-                }
-
-                // Then run periodically (every 2 minutes)
-                let mut interval = tokio::time::interval(std::time::Duration::from_secs(120));
-                loop {
-                    interval.tick().await;
-                    if let Some(db_state) = cleanup_handle.try_state::<db::DbState>() {
-                        match db_state.0.cleanup_stale_sessions(15) {
-                            Ok(count) if count > 0 => {
-                                // Notify frontend to refresh sessions
-                                let _ = cleanup_handle.emit("sessions-updated", ());
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to cleanup stale sessions: {}", e);
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            });
-
-            // Periodic Memgraph → SQLite sync (every 5 seconds)
-            // This ensures UI stays updated when hooks write to Memgraph
-            let sync_handle = handle.clone();
-            let periodic_graph_db = Arc::clone(&graph_db);
-            tauri::async_runtime::spawn(async move {
-                // Wait a bit for initial connection
-                tokio::time::sleep(std::time::Duration::from_secs(3)).await;
-
-                let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
-                loop {
-                    interval.tick().await;
-
-                    // Only sync if graph DB is connected
-                    if !periodic_graph_db.is_connected().await {
-                        continue;
-                    }
-
-                    if let Some(db_state) = sync_handle.try_state::<db::DbState>() {
-                        match sync_graph_to_sqlite(&periodic_graph_db, &db_state.0).await {
-                            Ok(count) if count > 0 => {
-                                tracing::debug!("Graph->SQLite sync: {} features", count);
-                                // Notify frontend to refresh data
-                                let _ = sync_handle.emit("features-updated", count);
-                            }
-                            Err(e) => {
-                                tracing::debug!("Graph sync skipped: {}", e);
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            });
+            let runtime = tauri::async_runtime::handle();
+            if daemon::is_running() {
+                tracing::info!(
+                    "Ijoka daemon already running on {:?}, attaching to it",
+                    server::get_socket_path()
+                );
+                daemon.attach(runtime.inner());
+            } else {
+                daemon.start(runtime.inner());
+            }
 
             // Setup system tray
             let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
@@ -251,11 +141,17 @@ fn main() {
             commands::watch_project,
             commands::get_config,
             commands::save_config,
+            commands::get_server_status,
             commands::get_plugin_status,
             commands::install_plugin,
             commands::get_plugin_path,
             commands::install_integration,
             commands::update_feature,
+            commands::get_feature_history,
+            commands::get_feature_locks,
+            commands::release_override,
+            commands::search,
+            commands::compact_database,
             // Graph database commands
             commands::get_graph_db_status,
             commands::get_graph_projects,
@@ -274,47 +170,3 @@ fn main() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
-
-/// Sync features from Graph DB to SQLite cache
-/// Returns the number of features synced
-async fn sync_graph_to_sqlite(
-    graph_db: &graph_db::GraphDb,
-    sqlite_db: &db::Database,
-) -> Result<usize, String> {
-    if !graph_db.is_connected().await {
-        return Err("Graph database not connected".to_string());
-    }
-
-    let projects = graph_db
-        .get_projects()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let mut synced_count = 0;
-
-    for project in &projects {
-        let features = graph_db
-            .get_features_for_project(&project.path)
-            .await
-            .map_err(|e| e.to_string())?;
-
-        for feature in features {
-            if let Some(id) = &feature.id {
-                let sync_feature = db::GraphFeatureSync {
-                    id: id.clone(),
-                    project_dir: project.path.clone(),
-                    description: feature.description.clone(),
-                    category: feature.category.clone(),
-                    status: feature.status.clone(),
-                    steps: feature.steps.clone().unwrap_or_default(),
-                };
-
-                if sqlite_db.sync_feature_from_graph(&sync_feature).is_ok() {
-                    synced_count += 1;
-                }
-            }
-        }
-    }
-
-    Ok(synced_count)
-}
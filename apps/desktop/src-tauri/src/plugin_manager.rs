@@ -3,6 +3,7 @@
 //! Manages the Ijoka plugin installation using the official Claude CLI.
 //! This is the production-ready approach that properly integrates with Claude Code.
 
+use crate::auth::{TokenStore, CLAUDE_CODE_AGENT};
 use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
@@ -75,6 +76,8 @@ impl PluginManager {
         match current_status {
             PluginStatus::Installed => {
                 tracing::info!("Plugin already installed and enabled");
+                // Keep the hook token current in case tokens.json was reset
+                self.enable_plugin_in_settings()?;
                 return Ok(PluginStatus::Installed);
             }
             PluginStatus::Disabled => {
@@ -191,8 +194,11 @@ impl PluginManager {
         Ok(())
     }
 
-    /// Enable the plugin in settings.json
+    /// Enable the plugin in settings.json and expose the hook ingestion
+    /// token to hooks through the `env` block
     fn enable_plugin_in_settings(&self) -> Result<(), String> {
+        let token = TokenStore::open_default().ensure_token(CLAUDE_CODE_AGENT)?;
+
         let settings_path = self.claude_dir.join("settings.json");
 
         let mut settings: Value = if settings_path.exists() {
@@ -212,6 +218,12 @@ impl PluginManager {
         // Enable our plugin
         settings["enabledPlugins"]["ijoka@Ijoka"] = serde_json::json!(true);
 
+        // Hooks authenticate to the ingestion server with this token
+        if !settings.get("env").is_some_and(Value::is_object) {
+            settings["env"] = serde_json::json!({});
+        }
+        settings["env"]["IJOKA_TOKEN"] = serde_json::json!(token);
+
         // Disable any old plugin identifiers (legacy AgentKanban names)
         let old_identifiers = [
             "agentkanban@AgentKanban",
@@ -1,3 +1,4 @@
+use crate::auth::{TokenStore, ANTIGRAVITY_AGENT};
 use anyhow::Result;
 use std::fs;
 use std::path::Path;
@@ -14,6 +15,12 @@ impl WorkflowService {
         let workflows_dir = root_path.join(".agent/workflows");
         let scripts_dir = root_path.join("scripts");
 
+        // The bridge reads its token from ~/.ijoka/tokens.json at runtime so no
+        // credential is written into the project tree
+        TokenStore::open_default()
+            .ensure_token(ANTIGRAVITY_AGENT)
+            .map_err(anyhow::Error::msg)?;
+
         // Ensure directories exist
         fs::create_dir_all(&workflows_dir)?;
         fs::create_dir_all(&scripts_dir)?;
@@ -34,16 +41,28 @@ impl WorkflowService {
     fn get_bridge_script_content() -> &'static str {
         r#"const { IjokaClient } = require('@ijoka/client');
 const fs = require('fs');
+const os = require('os');
 const path = require('path');
 
 // Simple persistent storage for session ID
 const SESSION_FILE = path.join(__dirname, '.current_session');
 
+// Ingestion token issued by the Ijoka app for this agent
+function readToken() {
+    if (process.env.IJOKA_TOKEN) return process.env.IJOKA_TOKEN;
+    try {
+        const tokens = JSON.parse(fs.readFileSync(path.join(os.homedir(), '.ijoka', 'tokens.json'), 'utf8'));
+        return tokens['antigravity'];
+    } catch (err) {
+        return undefined;
+    }
+}
+
 async function main() {
     const args = process.argv.slice(2);
     const command = args[0];
     // Default to localhost:4000
-    const client = new IjokaClient('http://127.0.0.1:4000');
+    const client = new IjokaClient('http://127.0.0.1:4000', readToken());
     const projectDir = process.cwd();
 
     switch (command) {
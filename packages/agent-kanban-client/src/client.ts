@@ -1,16 +1,18 @@
 import axios, { AxiosInstance } from 'axios';
-import { AgentEvent, SessionStart, FeatureUpdateEvent, SessionEnd, Feature } from './types';
+import { AgentEvent, SessionStart, FeatureUpdateEvent, SessionEnd, Feature, NewFeature, FeaturePatch, FeatureChange, SearchOptions, SearchResults } from './types';
 
 export class IjokaClient {
     private client: AxiosInstance;
     private baseUrl: string;
 
-    constructor(baseUrl: string = 'http://127.0.0.1:4000') {
+    constructor(baseUrl: string = 'http://127.0.0.1:4000', token?: string) {
         this.baseUrl = baseUrl;
+        const authToken = token ?? process.env.IJOKA_TOKEN;
         this.client = axios.create({
             baseURL: baseUrl,
             headers: {
                 'Content-Type': 'application/json',
+                ...(authToken ? { Authorization: `Bearer ${authToken}` } : {}),
             },
             timeout: 2000, // Short timeout to avoid hanging if server is down
         });
@@ -53,6 +55,82 @@ export class IjokaClient {
         }
     }
 
+    /**
+     * Create a feature in a project (instead of editing feature_list.json)
+     */
+    async createFeature(projectDir: string, feature: NewFeature): Promise<Feature | null> {
+        try {
+            const response = await this.client.post(this.featuresPath(projectDir), feature);
+            return response.data;
+        } catch (e) {
+            console.error('[Ijoka] Failed to create feature');
+            return null;
+        }
+    }
+
+    /**
+     * Change a feature's status or agent state. Refused (409) while a human
+     * has recently edited the feature.
+     */
+    async patchFeature(projectDir: string, featureId: string, patch: FeaturePatch): Promise<Feature | null> {
+        try {
+            const path = `${this.featuresPath(projectDir)}/${encodeURIComponent(featureId)}`;
+            const response = await this.client.patch(path, patch);
+            return response.data;
+        } catch (e) {
+            console.error('[Ijoka] Failed to update feature');
+            return null;
+        }
+    }
+
+    /**
+     * Delete a feature
+     */
+    async deleteFeature(projectDir: string, featureId: string): Promise<boolean> {
+        try {
+            await this.client.delete(`${this.featuresPath(projectDir)}/${encodeURIComponent(featureId)}`);
+            return true;
+        } catch (e) {
+            console.error('[Ijoka] Failed to delete feature');
+            return false;
+        }
+    }
+
+    /**
+     * A feature's recorded field changes and who made them, newest first
+     */
+    async getFeatureHistory(projectDir: string, featureId: string, limit?: number): Promise<FeatureChange[]> {
+        try {
+            const path = `${this.featuresPath(projectDir)}/${encodeURIComponent(featureId)}/history`;
+            const response = await this.client.get(path, { params: { limit } });
+            return response.data;
+        } catch (e) {
+            console.error('[Ijoka] Failed to fetch feature history');
+            return [];
+        }
+    }
+
+    /**
+     * Full-text search over event payloads and features, each ranked best
+     * match first in its own list
+     */
+    async search(query: string, options: SearchOptions = {}): Promise<SearchResults> {
+        try {
+            const params = {
+                q: query,
+                project_dir: options.projectDir,
+                from: options.from,
+                to: options.to,
+                limit: options.limit,
+            };
+            const response = await this.client.get('/search', { params });
+            return response.data;
+        } catch (e) {
+            console.error('[Ijoka] Failed to search');
+            return { events: [], features: [] };
+        }
+    }
+
     /**
      * Send an event (tool use, etc)
      */
@@ -60,6 +138,14 @@ export class IjokaClient {
         return this.postSafe('/events', event);
     }
 
+    /**
+     * Send many events at once (e.g. flushing an offline buffer).
+     * Stored in a single transaction; per-item failures are reported by the server.
+     */
+    async sendEvents(events: AgentEvent[]): Promise<boolean> {
+        return this.postSafe('/events/batch', events);
+    }
+
     /**
      * End a session
      */
@@ -78,6 +164,10 @@ export class IjokaClient {
         return this.postSafe('/events/feature-update', event);
     }
 
+    private featuresPath(projectDir: string): string {
+        return `/projects/${encodeURIComponent(projectDir)}/features`;
+    }
+
     private async postSafe(path: string, data: any): Promise<boolean> {
         try {
             await this.client.post(path, data);
@@ -6,6 +6,8 @@ export interface AgentEvent {
     toolName?: string;
     payload?: any;
     featureId?: string;
+    /** Stable id for this event; retries from the same agent with the same key are stored once */
+    idempotencyKey?: string;
 }
 
 export interface SessionStart {
@@ -39,6 +41,68 @@ export interface Feature {
     updatedAt: string;
 }
 
+export type FeatureStatus = 'pending' | 'in_progress' | 'complete';
+
+export interface NewFeature {
+    id?: string;
+    description: string;
+    category?: string;
+    status?: FeatureStatus;
+    priority?: number;
+    steps?: string[];
+}
+
+export interface FeaturePatch {
+    status?: FeatureStatus;
+    agent?: string;
+    confidence?: number;
+    model?: string;
+    hasError?: boolean;
+    /** Recorded in the feature history */
+    sessionId?: string;
+}
+
+export interface FeatureChange {
+    id: number;
+    featureId: string;
+    /** Column name, or 'status' for moves between pending/in_progress/complete */
+    field: string;
+    oldValue: string | null;
+    newValue: string | null;
+    source: 'human' | 'agent' | 'graph' | 'file';
+    agent: string | null;
+    sessionId: string | null;
+    changedAt: string;
+}
+
+export interface SearchOptions {
+    projectDir?: string;
+    /** RFC 3339 timestamp or YYYY-MM-DD */
+    from?: string;
+    to?: string;
+    limit?: number;
+}
+
+export interface SearchHit {
+    kind: 'event' | 'feature';
+    /** Event id or feature id */
+    id: string;
+    projectDir: string;
+    title: string;
+    sessionId: string | null;
+    timestamp: string;
+    /** Matched terms wrapped in <mark> tags; not HTML-escaped */
+    snippet: string;
+    /** BM25 score; lower is better. Only comparable within one list of SearchResults */
+    rank: number;
+}
+
+/** Search hits, ranked separately per index */
+export interface SearchResults {
+    events: SearchHit[];
+    features: SearchHit[];
+}
+
 export interface FeatureUpdateEvent {
     projectDir: string;
     stats: FeatureStats;
@@ -22,6 +22,8 @@ import graph_db_helper as db_helper
 from git_utils import resolve_project_path
 
 SYNC_SERVER = os.environ.get("IJOKA_SERVER", "http://127.0.0.1:4000")
+# Ingestion token, exported to hooks by the Ijoka app via Claude settings
+SYNC_TOKEN = os.environ.get("IJOKA_TOKEN", "")
 CACHE_DIR = Path.home() / ".cache" / "ijoka"
 
 
@@ -61,10 +63,14 @@ def send_to_server(project_dir: str, stats: dict, changed_features: list):
             "changedFeatures": changed_features
         }).encode()
         
+        headers = {"Content-Type": "application/json"}
+        if SYNC_TOKEN:
+            headers["Authorization"] = f"Bearer {SYNC_TOKEN}"
+
         req = Request(
             f"{SYNC_SERVER}/events/feature-update",
             data=data,
-            headers={"Content-Type": "application/json"},
+            headers=headers,
             method="POST"
         )
         
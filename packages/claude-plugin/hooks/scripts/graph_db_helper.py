@@ -549,6 +549,16 @@ def get_feature_claim(feature_id: str) -> Optional[dict]:
     }
 
 
+# Skips features whose status a human has locked from the desktop app or
+# the ingestion server (mirrored as FieldLock nodes; a null until holds the
+# lock until it is released)
+STATUS_UNLOCKED = """
+        OPTIONAL MATCH (f)-[:HAS_LOCK]->(l:FieldLock {field: 'status'})
+        WITH f, l
+        WHERE l IS NULL OR (l.until IS NOT NULL AND l.until < $now)
+"""
+
+
 def start_feature(
     feature_id: str,
     agent: Optional[str] = None,
@@ -565,7 +575,7 @@ def start_feature(
         force_override: Override existing active claims
 
     Returns:
-        Feature dict if successful, None if conflict or not found
+        Feature dict if successful, None if conflict, locked or not found
     """
     session_id = session_id or f"session-{int(datetime.now().timestamp() * 1000)}"
 
@@ -583,6 +593,7 @@ def start_feature(
     results = run_write_query(
         """
         MATCH (f:Feature {id: $featureId})
+        """ + STATUS_UNLOCKED + """
         SET f.status = 'in_progress',
             f.assigned_agent = $agent,
             f.claiming_session_id = $sessionId,
@@ -591,16 +602,18 @@ def start_feature(
             f.updated_at = datetime()
         RETURN f
         """,
-        {"featureId": feature_id, "agent": agent, "sessionId": session_id}
+        {"featureId": feature_id, "agent": agent, "sessionId": session_id, "now": _now_iso()}
     )
     return _node_to_dict(results[0], "f") if results else None
 
 
 def complete_feature(feature_id: str) -> Optional[dict]:
-    """Mark a feature as complete and clear claiming info."""
+    """Mark a feature as complete and clear claiming info. Returns None if
+    not found or its status is locked by a human."""
     results = run_write_query(
         """
         MATCH (f:Feature {id: $featureId})
+        """ + STATUS_UNLOCKED + """
         SET f.status = 'complete',
             f.completed_at = datetime(),
             f.updated_at = datetime(),
@@ -609,7 +622,7 @@ def complete_feature(feature_id: str) -> Optional[dict]:
             f.claimed_at = null
         RETURN f
         """,
-        {"featureId": feature_id}
+        {"featureId": feature_id, "now": _now_iso()}
     )
     return _node_to_dict(results[0], "f") if results else None
 
@@ -618,16 +631,18 @@ def activate_feature(project_dir: str, feature_id: str) -> bool:
     """
     Activate a feature (set to in_progress).
     Multiple features can be in_progress simultaneously.
-    Returns True if successful.
+    Returns True if successful, False if not found or its status is locked
+    by a human.
     """
     # Activate the target feature (no longer deactivates others)
     results = run_write_query(
         """
         MATCH (f:Feature {id: $featureId})
+        """ + STATUS_UNLOCKED + """
         SET f.status = 'in_progress', f.updated_at = datetime()
         RETURN f
         """,
-        {"featureId": feature_id}
+        {"featureId": feature_id, "now": _now_iso()}
     )
     return len(results) > 0
 
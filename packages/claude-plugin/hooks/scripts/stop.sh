@@ -14,9 +14,19 @@ SESSION_ID=$(echo "$INPUT" | jq -r '.session_id // empty')
 
 # Notify server of session stop
 SYNC_SERVER="${AGENTKANBAN_SERVER:-http://127.0.0.1:4000}"
+
+# Prefer the desktop app's Unix socket unless a server URL is set explicitly
+SYNC_SOCKET="${IJOKA_SOCKET:-$HOME/.ijoka/ijoka.sock}"
+CURL_OPTS=()
+if [ -z "${AGENTKANBAN_SERVER:-}" ] && [ -S "$SYNC_SOCKET" ]; then
+    SYNC_SERVER="http://localhost"
+    CURL_OPTS+=(--unix-socket "$SYNC_SOCKET")
+fi
+
 notify_server() {
-    curl -s -X POST "${SYNC_SERVER}/sessions/end" \
+    curl -s "${CURL_OPTS[@]}" -X POST "${SYNC_SERVER}/sessions/end" \
         -H "Content-Type: application/json" \
+        -H "Authorization: Bearer ${IJOKA_TOKEN:-}" \
         -d "{
             \"sessionId\": \"${SESSION_ID}\",
             \"sourceAgent\": \"claude-code\",
@@ -0,0 +1,201 @@
+//! Agent Authentication
+//!
+//! Per-agent bearer tokens for the hook ingestion server, stored in
+//! ~/.ijoka/tokens.json as `{ "<agent>": "<token>" }`. Integrations read their
+//! token from there (or from IJOKA_TOKEN) and send it as
+//! `Authorization: Bearer <token>`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::SystemTime;
+
+/// Agent identity for the Claude Code plugin hooks
+pub const CLAUDE_CODE_AGENT: &str = "claude-code";
+
+/// Agent identity for the Antigravity workflow bridge
+pub const ANTIGRAVITY_AGENT: &str = "antigravity";
+
+/// Get the standard token file path: ~/.ijoka/tokens.json
+pub fn get_tokens_path() -> PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".ijoka")
+        .join("tokens.json")
+}
+
+/// Agent tokens backed by a JSON file
+pub struct TokenStore {
+    path: PathBuf,
+    tokens: RwLock<LoadedTokens>,
+}
+
+#[derive(Default)]
+struct LoadedTokens {
+    /// token -> agent
+    by_token: HashMap<String, String>,
+    /// Modification time and length of the file when it was read
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl TokenStore {
+    /// Open the token file, treating a missing file as empty
+    pub fn open(path: PathBuf) -> Self {
+        let store = Self {
+            path,
+            tokens: RwLock::new(LoadedTokens::default()),
+        };
+        store.reload();
+        store
+    }
+
+    /// Open the standard token file
+    pub fn open_default() -> Self {
+        Self::open(get_tokens_path())
+    }
+
+    /// Resolve a token to its agent. On a miss the file is re-read if it
+    /// changed since it was loaded, so tokens issued after startup (e.g. by a
+    /// new integration) are accepted without every bad token hitting the disk.
+    pub fn agent_for(&self, token: &str) -> Option<String> {
+        if token.is_empty() {
+            return None;
+        }
+
+        let stamp = {
+            let tokens = self.tokens.read().unwrap();
+            if let Some(agent) = tokens.by_token.get(token) {
+                return Some(agent.clone());
+            }
+            tokens.stamp
+        };
+
+        if file_stamp(&self.path) == stamp {
+            return None;
+        }
+
+        self.reload();
+        self.tokens.read().unwrap().by_token.get(token).cloned()
+    }
+
+    /// Get the token for an agent, issuing and persisting one if needed
+    pub fn ensure_token(&self, agent: &str) -> Result<String, String> {
+        let mut by_agent = read_tokens_file(&self.path)?;
+
+        if let Some(token) = by_agent.get(agent) {
+            return Ok(token.clone());
+        }
+
+        let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        by_agent.insert(agent.to_string(), token.clone());
+        write_tokens_file(&self.path, &by_agent)?;
+
+        tracing::info!("Issued ingestion token for agent {}", agent);
+        self.reload();
+        Ok(token)
+    }
+
+    fn reload(&self) {
+        // Stamp before reading so a write racing the read is picked up next time
+        let stamp = file_stamp(&self.path);
+        match read_tokens_file(&self.path) {
+            Ok(by_agent) => {
+                let by_token = by_agent.into_iter().map(|(agent, token)| (token, agent)).collect();
+                *self.tokens.write().unwrap() = LoadedTokens { by_token, stamp };
+            }
+            Err(e) => {
+                tracing::error!("Failed to read agent tokens: {}", e);
+                // Don't retry a broken file until it changes again
+                self.tokens.write().unwrap().stamp = stamp;
+            }
+        }
+    }
+}
+
+/// Modification time and length of the token file, None if it is missing
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+fn read_tokens_file(path: &Path) -> Result<HashMap<String, String>, String> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+}
+
+fn write_tokens_file(path: &Path, by_agent: &HashMap<String, String>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+
+    let json = serde_json::to_string_pretty(by_agent)
+        .map_err(|e| format!("Failed to serialize tokens: {}", e))?;
+
+    // Tokens are credentials: write them to an owner-only temp file and rename
+    // it into place, so the file is never visible with wider permissions
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let written = options.open(&tmp).and_then(|mut file| {
+        use std::io::Write;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("Failed to write {:?}: {}", path, e));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_tokens_are_picked_up_when_the_file_changes() {
+        let dir = std::env::temp_dir().join(format!("ijoka-tokens-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("tokens.json");
+        let store = TokenStore::open(path.clone());
+
+        let first = store.ensure_token(CLAUDE_CODE_AGENT).unwrap();
+        assert_eq!(store.agent_for(&first).as_deref(), Some(CLAUDE_CODE_AGENT));
+        assert_eq!(store.agent_for("unknown"), None);
+
+        // Another process issues a token behind our back
+        let mut by_agent = read_tokens_file(&path).unwrap();
+        by_agent.insert(ANTIGRAVITY_AGENT.to_string(), "antigravity-token".to_string());
+        write_tokens_file(&path, &by_agent).unwrap();
+        assert_eq!(store.agent_for("antigravity-token").as_deref(), Some(ANTIGRAVITY_AGENT));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_token_file_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("ijoka-tokens-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("tokens.json");
+        TokenStore::open(path.clone()).ensure_token(CLAUDE_CODE_AGENT).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1, "temp file left behind");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
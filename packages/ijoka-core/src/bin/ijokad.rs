@@ -0,0 +1,51 @@
+//! ijokad - headless Ijoka daemon
+//!
+//! Runs the ingestion server, graph sync, file watcher and session cleanup
+//! without the desktop app, e.g. on a server or in a container.
+//!
+//! Usage:
+//!   ijokad        run the daemon until interrupted
+//!   ijokad mcp    serve MCP over stdio
+
+use ijoka_core::{daemon, mcp, server, Daemon, LogSink};
+use std::sync::Arc;
+
+fn main() {
+    if std::env::args().nth(1).as_deref() == Some("mcp") {
+        // stdout carries the protocol, so log to stderr
+        tracing_subscriber::fmt().with_writer(std::io::stderr).init();
+        if let Err(e) = mcp::run_stdio() {
+            tracing::error!("MCP server error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    tracing_subscriber::fmt::init();
+
+    if let Err(e) = run() {
+        tracing::error!("ijokad error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    if daemon::is_running() {
+        return Err(format!(
+            "another Ijoka instance is already serving {}",
+            server::get_socket_path().display()
+        )
+        .into());
+    }
+
+    let runtime = tokio::runtime::Runtime::new()?;
+
+    let daemon = Daemon::open(Arc::new(LogSink))?;
+    daemon.start(runtime.handle());
+
+    tracing::info!("ijokad running, press Ctrl+C to stop");
+    runtime.block_on(tokio::signal::ctrl_c())?;
+    tracing::info!("Shutting down");
+
+    Ok(())
+}
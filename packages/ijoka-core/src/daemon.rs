@@ -0,0 +1,408 @@
+//! Daemon
+//!
+//! Owns the shared stores and starts the background work: graph connection
+//! supervision and sync, the ingestion server, the file watcher, stale
+//! session cleanup and event retention. Used by the headless `ijokad` binary
+//! and embedded by the desktop app, which attaches to an already running
+//! daemon instead of starting a second one.
+
+use crate::db::{self, AgentEvent, ChangeMarker, Database, EventFilter};
+use crate::events::EventSink;
+use crate::graph_db::GraphDb;
+use crate::server::{self, ServerControl};
+use crate::{retention, sync, watcher};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::sync::broadcast;
+
+/// Sessions without activity for this long are marked ended
+const STALE_SESSION_MINUTES: i64 = 15;
+
+/// How often an attached instance checks that the running daemon is still up
+const ATTACHED_PROBE_SECS: u64 = 5;
+
+/// How often an attached instance looks for events and feature changes the
+/// running daemon stored
+const ATTACHED_POLL_MILLIS: u64 = 1000;
+
+/// Events read per query when an attached instance catches up
+const ATTACHED_EVENT_BATCH: i64 = 500;
+
+/// How often the config is re-read for a port saved by another instance
+const PORT_CONFIG_POLL_SECS: u64 = 2;
+
+#[derive(Clone)]
+pub struct Daemon {
+    pub db: Arc<Database>,
+    pub graph: Arc<GraphDb>,
+    pub server: Arc<ServerControl>,
+    /// Live feed of stored events
+    pub event_tx: Arc<broadcast::Sender<AgentEvent>>,
+    sink: Arc<dyn EventSink>,
+}
+
+impl Daemon {
+    /// Open the shared database at ~/.ijoka/ijoka.db and set up the graph
+    /// client. Nothing runs until `start`.
+    pub fn open(sink: Arc<dyn EventSink>) -> Result<Self, Box<dyn std::error::Error>> {
+        // Migrate from legacy AgentKanban location if needed
+        if let Err(e) = db::migrate_from_legacy() {
+            tracing::warn!("Database migration warning: {}", e);
+        }
+
+        // This path is shared with Claude Code hooks for single source of truth
+        let db_path = db::get_standard_db_path();
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        tracing::info!("Using shared database at {:?}", db_path);
+        let database = Arc::new(Database::new(&db_path)?);
+
+        // Memgraph is optional - the app works without it, using SQLite as fallback.
+        // Writes made while disconnected are queued in the SQLite outbox.
+        let graph = Arc::new(GraphDb::new().with_outbox(Arc::clone(&database)));
+
+        let port = database
+            .get_config()
+            .map(|config| config.sync_server_port)
+            .unwrap_or_else(|_| db::Config::default().sync_server_port);
+
+        let (event_tx, _) = broadcast::channel(100);
+
+        Ok(Self {
+            db: database,
+            graph,
+            server: Arc::new(ServerControl::new(port)),
+            event_tx: Arc::new(event_tx),
+            sink,
+        })
+    }
+
+    /// Start all background work on the given runtime
+    pub fn start(&self, runtime: &Handle) {
+        self.start_graph(runtime);
+        self.start_services(runtime);
+    }
+
+    /// Defer to the daemon already serving ~/.ijoka/ijoka.sock (see
+    /// `is_running`): share its database and graph, but leave ingestion, sync,
+    /// the file watcher, session cleanup and retention to it. The events and
+    /// feature changes it stores are emitted here as they appear in the
+    /// shared database, and port changes saved here are picked up by it.
+    /// Starts the work here if that daemon goes away.
+    pub fn attach(&self, runtime: &Handle) {
+        self.start_graph(runtime);
+
+        let daemon = self.clone();
+        runtime.spawn(async move {
+            let mut seen = match daemon.db.run(|db| db.get_change_marker()).await {
+                Ok(marker) => marker,
+                Err(e) => {
+                    tracing::warn!("Failed to read the shared database: {}", e);
+                    ChangeMarker::default()
+                }
+            };
+
+            let mut poll = tokio::time::interval(Duration::from_millis(ATTACHED_POLL_MILLIS));
+            let mut probe = tokio::time::interval(Duration::from_secs(ATTACHED_PROBE_SECS));
+            loop {
+                tokio::select! {
+                    _ = poll.tick() => {
+                        seen = emit_shared_changes(&daemon.db, daemon.sink.as_ref(), seen).await;
+                    }
+                    _ = probe.tick() => {
+                        if !tokio::task::spawn_blocking(is_running).await.unwrap_or(false) {
+                            tracing::info!("Running Ijoka daemon went away, taking over its work");
+                            daemon.start_services(&Handle::current());
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Keep the Memgraph connection alive and report its status
+    fn start_graph(&self, runtime: &Handle) {
+        // Connects, health-checks and reconnects with backoff
+        let graph = Arc::clone(&self.graph);
+        runtime.spawn(async move { graph.supervise().await });
+
+        runtime.spawn(sync::forward_graph_status(
+            Arc::clone(&self.graph),
+            Arc::clone(&self.db),
+            Arc::clone(&self.sink),
+        ));
+    }
+
+    /// Ingestion, graph -> SQLite sync, file watching and housekeeping. Only
+    /// one instance runs these against the shared database.
+    fn start_services(&self, runtime: &Handle) {
+        runtime.spawn(sync::run_periodic_sync(
+            Arc::clone(&self.graph),
+            Arc::clone(&self.db),
+            Arc::clone(&self.sink),
+        ));
+
+        // HTTP server for hook events on the configured port and ~/.ijoka/ijoka.sock
+        runtime.spawn(server::start_server(
+            Arc::clone(&self.db),
+            Arc::clone(&self.graph),
+            Arc::clone(&self.sink),
+            Arc::clone(&self.event_tx),
+            Arc::clone(&self.server),
+        ));
+        runtime.spawn(follow_port_config(Arc::clone(&self.db), Arc::clone(&self.server)));
+
+        // File watcher blocks, so it gets its own thread
+        let watcher_db = Arc::clone(&self.db);
+        let watcher_sink = Arc::clone(&self.sink);
+        let watcher_tx = Arc::clone(&self.event_tx);
+        std::thread::spawn(move || {
+            if let Err(e) = watcher::start_watching(watcher_db, watcher_sink, watcher_tx) {
+                tracing::error!("File watcher error: {}", e);
+            }
+        });
+
+        // Forward stored events to the UI
+        let mut event_rx = self.event_tx.subscribe();
+        let event_sink = Arc::clone(&self.sink);
+        runtime.spawn(async move {
+            loop {
+                match event_rx.recv().await {
+                    Ok(event) => event_sink.emit("agent-event", json!(event)),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        runtime.spawn(cleanup_stale_sessions(Arc::clone(&self.db), Arc::clone(&self.sink)));
+
+        // Roll up, archive and delete events past the retention policy
+        runtime.spawn(retention::run_retention_job(Arc::clone(&self.db), Arc::clone(&self.sink)));
+    }
+}
+
+/// Whether another Ijoka daemon (ijokad or a desktop app) is answering on
+/// ~/.ijoka/ijoka.sock. A stale socket file left by a crash does not count.
+#[cfg(unix)]
+pub fn is_running() -> bool {
+    is_serving(&server::get_socket_path())
+}
+
+/// Whether the socket at `path` answers `/health`
+#[cfg(unix)]
+fn is_serving(path: &std::path::Path) -> bool {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    let Ok(mut stream) = UnixStream::connect(path) else {
+        return false;
+    };
+    let timeout = Some(Duration::from_secs(1));
+    if stream.set_read_timeout(timeout).is_err() || stream.set_write_timeout(timeout).is_err() {
+        return false;
+    }
+
+    // HTTP/1.0 so the server closes the connection after responding
+    if stream.write_all(b"GET /health HTTP/1.0\r\nHost: localhost\r\n\r\n").is_err() {
+        return false;
+    }
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    response.split_whitespace().nth(1) == Some("200")
+}
+
+/// Whether another Ijoka daemon is running. Without Unix sockets there is
+/// nothing to probe, so every instance runs its own.
+#[cfg(not(unix))]
+pub fn is_running() -> bool {
+    false
+}
+
+/// Emit what another process stored since `seen`: each new event as
+/// `agent-event` and one `features-updated` for any feature change. Returns
+/// the marker to compare the next poll against.
+async fn emit_shared_changes(db: &Arc<Database>, sink: &dyn EventSink, seen: ChangeMarker) -> ChangeMarker {
+    let current = match db.run(|db| db.get_change_marker()).await {
+        Ok(marker) => marker,
+        Err(e) => {
+            tracing::warn!("Failed to poll the shared database: {}", e);
+            return seen;
+        }
+    };
+
+    let mut last_event_id = seen.last_event_id;
+    while last_event_id < current.last_event_id {
+        let after = last_event_id;
+        let events = match db
+            .run(move |db| db.get_events_after(after, &EventFilter::default(), ATTACHED_EVENT_BATCH))
+            .await
+        {
+            Ok(events) => events,
+            Err(e) => {
+                tracing::warn!("Failed to read new events: {}", e);
+                return ChangeMarker { last_event_id, ..seen };
+            }
+        };
+        // Nothing left means the rest was compacted away in the meantime
+        let Some(last) = events.last().and_then(|event| event.id) else {
+            last_event_id = current.last_event_id;
+            break;
+        };
+        for event in events {
+            sink.emit("agent-event", json!(event));
+        }
+        last_event_id = last;
+    }
+
+    if current.features != seen.features {
+        sink.emit("features-updated", json!(null));
+    }
+
+    ChangeMarker {
+        last_event_id,
+        features: current.features,
+    }
+}
+
+/// Move the listener when another instance (e.g. a desktop app attached to
+/// this daemon) saves a different port to the shared config
+async fn follow_port_config(db: Arc<Database>, server: Arc<ServerControl>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(PORT_CONFIG_POLL_SECS));
+    loop {
+        interval.tick().await;
+        match db.run(|db| db.get_config()).await {
+            Ok(config) => server.set_port(config.sync_server_port),
+            Err(e) => tracing::warn!("Failed to read config: {}", e),
+        }
+    }
+}
+
+/// Mark stale sessions ended on startup and then every 2 minutes
+async fn cleanup_stale_sessions(db: Arc<Database>, sink: Arc<dyn EventSink>) {
+    match db.run(|db| db.cleanup_stale_sessions(STALE_SESSION_MINUTES)).await {
+        Ok(count) if count > 0 => {
+            tracing::info!("Startup cleanup: marked {} stale sessions as ended", count);
+        }
+        _ => {}
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_secs(120));
+    loop {
+        interval.tick().await;
+        match db.run(|db| db.cleanup_stale_sessions(STALE_SESSION_MINUTES)).await {
+            Ok(count) if count > 0 => {
+                // Notify frontend to refresh sessions
+                sink.emit("sessions-updated", json!(null));
+            }
+            Err(e) => {
+                tracing::error!("Failed to cleanup stale sessions: {}", e);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{event, graph_feature};
+    use std::sync::Mutex;
+
+    /// Records emitted event names with the event id, if any
+    #[derive(Default)]
+    struct RecordingSink(Mutex<Vec<(String, Option<i64>)>>);
+
+    impl EventSink for RecordingSink {
+        fn emit(&self, event: &str, payload: serde_json::Value) {
+            let id = payload.get("id").and_then(|id| id.as_i64());
+            self.0.lock().unwrap().push((event.to_string(), id));
+        }
+
+        fn notify(&self, _title: &str, _body: &str) {}
+    }
+
+    impl RecordingSink {
+        fn take(&self) -> Vec<(String, Option<i64>)> {
+            std::mem::take(&mut self.0.lock().unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_attached_instance_emits_what_the_daemon_stores() {
+        let db = Arc::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        let sink = RecordingSink::default();
+        let seen = db.get_change_marker().unwrap();
+
+        let seen = emit_shared_changes(&db, &sink, seen).await;
+        assert!(sink.take().is_empty());
+
+        // The running daemon stores events and syncs a feature
+        db.insert_event(&event("Bash", "/a", "{}")).unwrap();
+        db.insert_event(&event("Read", "/a", "{}")).unwrap();
+        db.sync_feature_from_graph(&graph_feature("f1", "/a")).unwrap();
+
+        let seen = emit_shared_changes(&db, &sink, seen).await;
+        let agent_event = |id| ("agent-event".to_string(), Some(id));
+        assert_eq!(
+            sink.take(),
+            [agent_event(1), agent_event(2), ("features-updated".to_string(), None)]
+        );
+
+        db.release_override("f1", None).unwrap();
+        db.delete_feature("f1", db::UpdateSource::Human).unwrap();
+        emit_shared_changes(&db, &sink, seen).await;
+        assert_eq!(sink.take(), [("features-updated".to_string(), None)]);
+    }
+
+    #[tokio::test]
+    async fn test_port_saved_by_another_instance_moves_the_listener() {
+        let db = Arc::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        let mut config = db.get_config().unwrap();
+        let server = Arc::new(ServerControl::new(config.sync_server_port));
+
+        config.sync_server_port += 1;
+        db.save_config(&config).unwrap();
+        let follower = tokio::spawn(follow_port_config(Arc::clone(&db), Arc::clone(&server)));
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while server.port() != config.sync_server_port {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("port change was not picked up");
+        follower.abort();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_only_a_live_socket_counts_as_running() {
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixListener;
+
+        let dir = std::env::temp_dir().join(format!("ijoka-daemon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ijoka.sock");
+        assert!(!is_serving(&path));
+
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 256];
+            let _ = stream.read(&mut request).unwrap();
+            stream.write_all(b"HTTP/1.0 200 OK\r\ncontent-length: 2\r\n\r\nOK").unwrap();
+        });
+        assert!(is_serving(&path));
+        server.join().unwrap();
+
+        // The socket file of an instance that is gone
+        assert!(!is_serving(&path));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,2343 @@
+use crate::pool::Pool;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Read-only connections kept open alongside the writer
+const READER_CONNECTIONS: usize = 4;
+
+pub struct Database {
+    pool: Pool,
+}
+
+pub struct DbState(pub Arc<Database>);
+
+/// Get the standard database path: ~/.ijoka/ijoka.db
+/// This is shared between Tauri app and Claude Code hooks
+pub fn get_standard_db_path() -> PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".ijoka")
+        .join("ijoka.db")
+}
+
+/// Migrate database from legacy AgentKanban location if needed
+pub fn migrate_from_legacy() -> Result<(), Box<dyn std::error::Error>> {
+    let home = dirs::home_dir().ok_or("No home directory")?;
+    let legacy_dir = home.join(".agentkanban");
+    let legacy_db = legacy_dir.join("agentkanban.db");
+    let new_dir = home.join(".ijoka");
+    let new_db = new_dir.join("ijoka.db");
+
+    // Only migrate if legacy exists and new doesn't
+    if legacy_db.exists() && !new_db.exists() {
+        tracing::info!("Migrating database from AgentKanban to Ijoka...");
+
+        // Create new directory
+        std::fs::create_dir_all(&new_dir)?;
+
+        // Copy database file
+        std::fs::copy(&legacy_db, &new_db)?;
+
+        // Copy WAL and SHM files if they exist
+        let legacy_wal = legacy_dir.join("agentkanban.db-wal");
+        let legacy_shm = legacy_dir.join("agentkanban.db-shm");
+        if legacy_wal.exists() {
+            std::fs::copy(&legacy_wal, new_dir.join("ijoka.db-wal"))?;
+        }
+        if legacy_shm.exists() {
+            std::fs::copy(&legacy_shm, new_dir.join("ijoka.db-shm"))?;
+        }
+
+        tracing::info!("Migration complete. Legacy data preserved at {:?}", legacy_dir);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentEvent {
+    pub id: Option<i64>,
+    pub event_type: String,
+    pub source_agent: String,
+    pub session_id: String,
+    pub project_dir: String,
+    pub tool_name: Option<String>,
+    pub payload: Option<String>,
+    pub feature_id: Option<String>,
+    pub created_at: String,
+    /// Client-supplied key that makes retried submissions idempotent, unique
+    /// per source agent; the graph `Event.id` is `<agent>:<key>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    #[serde(flatten)]
+    pub details: EventDetails,
+}
+
+/// Typed hook fields extracted from the event payload
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDetails {
+    /// Tool input as JSON text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_input: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Outcome of inserting an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertedEvent {
+    New(i64),
+    /// Idempotency key already stored; holds the existing row's id
+    Duplicate(i64),
+}
+
+impl InsertedEvent {
+    pub fn id(&self) -> i64 {
+        match self {
+            InsertedEvent::New(id) | InsertedEvent::Duplicate(id) => *id,
+        }
+    }
+
+    pub fn is_duplicate(&self) -> bool {
+        matches!(self, InsertedEvent::Duplicate(_))
+    }
+}
+
+fn insert_event_row(conn: &Connection, event: &AgentEvent) -> Result<InsertedEvent, rusqlite::Error> {
+    let rows = conn.execute(
+        "INSERT INTO events (event_type, source_agent, session_id, project_dir, tool_name, payload, feature_id, idempotency_key,
+                             tool_input, exit_code, success, duration_ms, summary)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+         ON CONFLICT(source_agent, idempotency_key) DO NOTHING",
+        params![
+            event.event_type,
+            event.source_agent,
+            event.session_id,
+            event.project_dir,
+            event.tool_name,
+            event.payload,
+            event.feature_id,
+            event.idempotency_key,
+            event.details.tool_input,
+            event.details.exit_code,
+            event.details.success,
+            event.details.duration_ms,
+            event.details.summary,
+        ],
+    )?;
+
+    if rows > 0 {
+        return Ok(InsertedEvent::New(conn.last_insert_rowid()));
+    }
+
+    let id = conn.query_row(
+        "SELECT id FROM events WHERE source_agent = ?1 AND idempotency_key = ?2",
+        params![event.source_agent, event.idempotency_key],
+        |row| row.get(0),
+    )?;
+    Ok(InsertedEvent::Duplicate(id))
+}
+
+/// Optional event filters; `None` matches everything
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EventFilter {
+    pub project_dir: Option<String>,
+    pub session_id: Option<String>,
+    pub event_type: Option<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &AgentEvent) -> bool {
+        self.project_dir.as_ref().is_none_or(|p| *p == event.project_dir)
+            && self.session_id.as_ref().is_none_or(|s| *s == event.session_id)
+            && self.event_type.as_ref().is_none_or(|t| *t == event.event_type)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Feature {
+    pub id: String,
+    pub project_dir: String,
+    pub description: String,
+    pub category: String,
+    pub passes: bool,
+    pub in_progress: bool,
+    pub agent: Option<String>,
+    pub steps: Option<Vec<String>>,
+    pub work_count: i32,
+    pub completion_criteria: Option<String>,
+    pub updated_at: String,
+    // Agent-managed state
+    pub confidence: Option<i32>,         // 0-100, agent's estimate of completion
+    pub model: Option<String>,           // Which model is working on it (e.g., "claude-3.5-sonnet")
+    pub is_streaming: bool,              // Currently generating output
+    pub retry_count: i32,                // Loop detection - increments on repeated failures
+    pub token_cost: Option<i64>,         // Running token cost
+    pub has_error: bool,                 // Error state for visual indicator
+    pub last_agent_update: Option<String>, // Timestamp of last agent update
+    // Human override state
+    pub manual_priority: Option<String>, // "high" | "normal" - human override for priority
+    pub human_override_until: Option<String>, // Latest timed lock expiry, for display (locks live in feature_locks)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub session_id: String,
+    pub source_agent: String,
+    pub project_dir: String,
+    pub started_at: String,
+    pub last_activity: String,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub total: i64,
+    pub completed: i64,
+    pub in_progress: i64,
+    pub percentage: f64,
+    pub active_sessions: i64,
+}
+
+/// Full-text search over events and features
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    /// Free text; every term must match. A trailing `*` makes a term a prefix.
+    pub text: String,
+    pub project_dir: Option<String>,
+    /// Inclusive bounds in SQLite datetime format, see `parse_search_time`
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: i64,
+}
+
+impl SearchQuery {
+    pub const DEFAULT_LIMIT: i64 = 20;
+    pub const MAX_LIMIT: i64 = 200;
+
+    /// Build a query from user input, rejecting unparseable time bounds
+    pub fn parse(
+        text: &str,
+        project_dir: Option<String>,
+        from: Option<&str>,
+        to: Option<&str>,
+        limit: Option<i64>,
+    ) -> Result<Self, String> {
+        let bound = |value: Option<&str>, end_of_day: bool| {
+            value
+                .filter(|v| !v.is_empty())
+                .map(|v| {
+                    parse_search_time(v, end_of_day)
+                        .ok_or_else(|| format!("Invalid time {:?}; expected RFC 3339 or YYYY-MM-DD", v))
+                })
+                .transpose()
+        };
+
+        Ok(Self {
+            text: text.to_string(),
+            project_dir: project_dir.filter(|p| !p.is_empty()),
+            from: bound(from, false)?,
+            to: bound(to, true)?,
+            limit: limit.unwrap_or(Self::DEFAULT_LIMIT).clamp(1, Self::MAX_LIMIT),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchHitKind {
+    Event,
+    Feature,
+}
+
+/// A ranked search result. `snippet` wraps matched terms in `<mark>` tags
+/// but is otherwise raw text, not escaped HTML.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    /// Event id or feature id
+    pub id: String,
+    pub project_dir: String,
+    /// Tool name (or event type) for events, description for features
+    pub title: String,
+    pub session_id: Option<String>,
+    /// Event creation or feature update time
+    pub timestamp: String,
+    pub snippet: String,
+    /// BM25 score; lower is a better match. Only comparable with hits of
+    /// the same kind, since each index has its own statistics.
+    pub rank: f64,
+}
+
+/// Search results, ranked separately per index
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    pub events: Vec<SearchHit>,
+    pub features: Vec<SearchHit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    pub watched_projects: Vec<String>,
+    pub sync_server_port: u16,
+    pub notifications_enabled: bool,
+    pub selected_project: Option<String>,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    #[serde(default)]
+    pub human_override: HumanOverridePolicy,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            watched_projects: vec![],
+            sync_server_port: 4000,
+            notifications_enabled: true,
+            selected_project: None,
+            retention: RetentionPolicy::default(),
+            human_override: HumanOverridePolicy::default(),
+        }
+    }
+}
+
+/// When raw events are compacted into rollups and removed from the database.
+/// Off by default: nothing expires until a limit is configured.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionPolicy {
+    /// Events older than this many days expire; None keeps them regardless of age
+    pub max_age_days: Option<u32>,
+    /// Only the newest this many events per project are kept; None for no limit
+    pub max_rows_per_project: Option<u32>,
+    /// Write expired events to compressed NDJSON in ~/.ijoka/archive before deleting them
+    pub archive: bool,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: None,
+            max_rows_per_project: None,
+            archive: true,
+        }
+    }
+}
+
+/// How long a human edit keeps agents off the fields it touched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HumanOverridePolicy {
+    /// Lock length in minutes; None holds locks until they are released
+    pub lock_minutes: Option<u32>,
+}
+
+impl Default for HumanOverridePolicy {
+    fn default() -> Self {
+        Self { lock_minutes: Some(5) }
+    }
+}
+
+/// A human lock on one feature field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldLock {
+    pub feature_id: String,
+    /// Field name as in `FeatureUpdate::fields`
+    pub field: String,
+    /// RFC 3339; None until released
+    pub locked_until: Option<String>,
+    pub locked_at: String,
+}
+
+/// Where the shared tables stood at one point, compared between polls to
+/// notice writes made by another process
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeMarker {
+    /// Highest stored event id
+    pub last_event_id: i64,
+    /// Opaque; differs once a feature or one of its locks is added, changed
+    /// or removed
+    pub features: String,
+}
+
+/// Event counts for one session, day, event type and tool, kept after the
+/// raw events are compacted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventRollup {
+    pub project_dir: String,
+    pub session_id: String,
+    /// YYYY-MM-DD (UTC)
+    pub day: String,
+    pub event_type: String,
+    pub tool_name: Option<String>,
+    pub source_agent: String,
+    pub event_count: i64,
+    pub failure_count: i64,
+    pub total_duration_ms: i64,
+    pub first_at: String,
+    pub last_at: String,
+}
+
+/// One field change from `feature_history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureChange {
+    pub id: i64,
+    pub feature_id: String,
+    /// Column name, or "status" for moves between pending/in_progress/complete
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    /// "human", "agent", "graph" for changes pulled in by graph sync, or
+    /// "file" for changes read from feature_list.json
+    pub source: String,
+    pub agent: Option<String>,
+    pub session_id: Option<String>,
+    pub changed_at: String,
+}
+
+impl Database {
+    pub fn new(path: &Path) -> Result<Self, rusqlite::Error> {
+        let mut conn = Connection::open(path)?;
+
+        // Configure SQLite for concurrent access (WAL mode)
+        // This allows hooks and Tauri app to safely share the database
+        conn.execute_batch(
+            r#"
+            PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+            PRAGMA busy_timeout = 10000;
+            PRAGMA cache_size = -2000;
+            "#,
+        )?;
+
+        tracing::info!("Database opened with WAL mode at {:?}", path);
+
+        // Create and upgrade the schema; fails loudly instead of ignoring errors
+        crate::migrations::run(&mut conn)?;
+
+        // Readers are opened after migrating so they see the current schema
+        Ok(Self {
+            pool: Pool::new(conn, path, READER_CONNECTIONS)?,
+        })
+    }
+
+    /// Run blocking database work on tokio's blocking pool, so async callers
+    /// don't stall a runtime worker while SQLite is busy
+    pub async fn run<T, F>(self: &Arc<Self>, f: F) -> T
+    where
+        F: FnOnce(&Database) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let db = Arc::clone(self);
+        match tokio::task::spawn_blocking(move || f(&db)).await {
+            Ok(result) => result,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => panic!("database task cancelled: {}", e),
+        }
+    }
+
+    /// Insert an event. An event whose idempotency key was already stored for
+    /// the same agent is not inserted again; the existing row's id is
+    /// returned as a duplicate.
+    pub fn insert_event(&self, event: &AgentEvent) -> Result<InsertedEvent, rusqlite::Error> {
+        let conn = self.pool.write();
+        insert_event_row(&conn, event)
+    }
+
+    /// Insert events in a single transaction, returning their outcomes in order.
+    /// Either all events are stored or none are.
+    pub fn insert_events(&self, events: &[AgentEvent]) -> Result<Vec<InsertedEvent>, rusqlite::Error> {
+        let mut conn = self.pool.write();
+        let tx = conn.transaction()?;
+
+        let inserted = events
+            .iter()
+            .map(|event| insert_event_row(&tx, event))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    pub fn get_events(&self, limit: i64) -> Result<Vec<AgentEvent>, rusqlite::Error> {
+        let conn = self.pool.read();
+        let mut stmt = conn.prepare(
+            "SELECT id, event_type, source_agent, session_id, project_dir, tool_name, payload, feature_id, created_at, idempotency_key,
+                   tool_input, exit_code, success, duration_ms, summary
+             FROM events ORDER BY created_at DESC LIMIT ?1",
+        )?;
+
+        let events = stmt
+            .query_map([limit], |row| {
+                Ok(AgentEvent {
+                    id: Some(row.get(0)?),
+                    event_type: row.get(1)?,
+                    source_agent: row.get(2)?,
+                    session_id: row.get(3)?,
+                    project_dir: row.get(4)?,
+                    tool_name: row.get(5)?,
+                    payload: row.get(6)?,
+                    feature_id: row.get(7)?,
+                    created_at: row.get(8)?,
+                    idempotency_key: row.get(9)?,
+                    details: EventDetails {
+                        tool_input: row.get(10)?,
+                        exit_code: row.get(11)?,
+                        success: row.get(12)?,
+                        duration_ms: row.get(13)?,
+                        summary: row.get(14)?,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(events)
+    }
+
+    pub fn get_events_by_feature(&self, feature_id: &str, limit: i64) -> Result<Vec<AgentEvent>, rusqlite::Error> {
+        let conn = self.pool.read();
+        let mut stmt = conn.prepare(
+            "SELECT id, event_type, source_agent, session_id, project_dir, tool_name, payload, feature_id, created_at, idempotency_key,
+                   tool_input, exit_code, success, duration_ms, summary
+             FROM events WHERE feature_id = ?1 ORDER BY created_at DESC LIMIT ?2",
+        )?;
+
+        let events = stmt
+            .query_map(params![feature_id, limit], |row| {
+                Ok(AgentEvent {
+                    id: Some(row.get(0)?),
+                    event_type: row.get(1)?,
+                    source_agent: row.get(2)?,
+                    session_id: row.get(3)?,
+                    project_dir: row.get(4)?,
+                    tool_name: row.get(5)?,
+                    payload: row.get(6)?,
+                    feature_id: row.get(7)?,
+                    created_at: row.get(8)?,
+                    idempotency_key: row.get(9)?,
+                    details: EventDetails {
+                        tool_input: row.get(10)?,
+                        exit_code: row.get(11)?,
+                        success: row.get(12)?,
+                        duration_ms: row.get(13)?,
+                        summary: row.get(14)?,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(events)
+    }
+
+    /// Get events with an id greater than `after_id`, oldest first, optionally
+    /// filtered. Used to replay missed events to stream subscribers.
+    pub fn get_events_after(
+        &self,
+        after_id: i64,
+        filter: &EventFilter,
+        limit: i64,
+    ) -> Result<Vec<AgentEvent>, rusqlite::Error> {
+        let conn = self.pool.read();
+        let mut stmt = conn.prepare(
+            "SELECT id, event_type, source_agent, session_id, project_dir, tool_name, payload, feature_id, created_at, idempotency_key,
+                   tool_input, exit_code, success, duration_ms, summary
+             FROM events
+             WHERE id > ?1
+               AND (?2 IS NULL OR project_dir = ?2)
+               AND (?3 IS NULL OR session_id = ?3)
+               AND (?4 IS NULL OR event_type = ?4)
+             ORDER BY id ASC LIMIT ?5",
+        )?;
+
+        let events = stmt
+            .query_map(
+                params![after_id, filter.project_dir, filter.session_id, filter.event_type, limit],
+                |row| {
+                    Ok(AgentEvent {
+                        id: Some(row.get(0)?),
+                        event_type: row.get(1)?,
+                        source_agent: row.get(2)?,
+                        session_id: row.get(3)?,
+                        project_dir: row.get(4)?,
+                        tool_name: row.get(5)?,
+                        payload: row.get(6)?,
+                        feature_id: row.get(7)?,
+                        created_at: row.get(8)?,
+                        idempotency_key: row.get(9)?,
+                        details: EventDetails {
+                            tool_input: row.get(10)?,
+                            exit_code: row.get(11)?,
+                            success: row.get(12)?,
+                            duration_ms: row.get(13)?,
+                            summary: row.get(14)?,
+                        },
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(events)
+    }
+
+    /// Search event payloads and feature descriptions. Each kind is ranked
+    /// best match first within its own list of up to `limit` hits; BM25
+    /// scores from the two indexes are not comparable, so they aren't merged.
+    pub fn search(&self, query: &SearchQuery) -> Result<SearchResults, rusqlite::Error> {
+        let Some(fts) = fts_query(&query.text) else {
+            return Ok(SearchResults::default());
+        };
+        let conn = self.pool.read();
+        let filters = params![fts, query.project_dir, query.from, query.to, query.limit];
+
+        let mut stmt = conn.prepare(
+            "SELECT e.id, e.project_dir, COALESCE(e.tool_name, e.event_type), e.session_id, e.created_at,
+                    snippet(events_fts, -1, '<mark>', '</mark>', '…', 16), bm25(events_fts)
+             FROM events_fts JOIN events e ON e.id = events_fts.rowid
+             WHERE events_fts MATCH ?1
+               AND (?2 IS NULL OR e.project_dir = ?2)
+               AND (?3 IS NULL OR datetime(e.created_at) >= ?3)
+               AND (?4 IS NULL OR datetime(e.created_at) <= ?4)
+             ORDER BY bm25(events_fts) LIMIT ?5",
+        )?;
+        let events = stmt
+            .query_map(filters, |row| {
+                Ok(SearchHit {
+                    kind: SearchHitKind::Event,
+                    id: row.get::<_, i64>(0)?.to_string(),
+                    project_dir: row.get(1)?,
+                    title: row.get(2)?,
+                    session_id: row.get(3)?,
+                    timestamp: row.get(4)?,
+                    snippet: row.get(5)?,
+                    rank: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT f.id, f.project_dir, f.description, f.updated_at,
+                    snippet(features_fts, -1, '<mark>', '</mark>', '…', 16), bm25(features_fts)
+             FROM features_fts JOIN features f ON f.id = features_fts.feature_id
+             WHERE features_fts MATCH ?1
+               AND (?2 IS NULL OR f.project_dir = ?2)
+               AND (?3 IS NULL OR datetime(f.updated_at) >= ?3)
+               AND (?4 IS NULL OR datetime(f.updated_at) <= ?4)
+             ORDER BY bm25(features_fts) LIMIT ?5",
+        )?;
+        let features = stmt
+            .query_map(filters, |row| {
+                Ok(SearchHit {
+                    kind: SearchHitKind::Feature,
+                    id: row.get(0)?,
+                    project_dir: row.get(1)?,
+                    title: row.get(2)?,
+                    session_id: None,
+                    timestamp: row.get(3)?,
+                    snippet: row.get(4)?,
+                    rank: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SearchResults { events, features })
+    }
+
+    /// Events past the retention policy, oldest first: older than the max age,
+    /// or beyond the newest `max_rows_per_project` of their project
+    pub fn get_expired_events(
+        &self,
+        policy: &RetentionPolicy,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> Result<Vec<AgentEvent>, rusqlite::Error> {
+        let cutoff = policy.max_age_days.map(|days| {
+            (now - chrono::Duration::days(days.into()))
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        });
+        if cutoff.is_none() && policy.max_rows_per_project.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.pool.read();
+        let mut stmt = conn.prepare(
+            "SELECT id, event_type, source_agent, session_id, project_dir, tool_name, payload, feature_id, created_at, idempotency_key,
+                   tool_input, exit_code, success, duration_ms, summary
+             FROM events
+             WHERE id IN (
+                 SELECT id FROM (
+                     SELECT id, created_at,
+                            ROW_NUMBER() OVER (PARTITION BY project_dir ORDER BY id DESC) AS newest
+                     FROM events
+                 )
+                 WHERE (?1 IS NOT NULL AND datetime(created_at) < ?1)
+                    OR (?2 IS NOT NULL AND newest > ?2)
+             )
+             ORDER BY id ASC LIMIT ?3",
+        )?;
+
+        let events = stmt
+            .query_map(params![cutoff, policy.max_rows_per_project, limit], |row| {
+                Ok(AgentEvent {
+                    id: Some(row.get(0)?),
+                    event_type: row.get(1)?,
+                    source_agent: row.get(2)?,
+                    session_id: row.get(3)?,
+                    project_dir: row.get(4)?,
+                    tool_name: row.get(5)?,
+                    payload: row.get(6)?,
+                    feature_id: row.get(7)?,
+                    created_at: row.get(8)?,
+                    idempotency_key: row.get(9)?,
+                    details: EventDetails {
+                        tool_input: row.get(10)?,
+                        exit_code: row.get(11)?,
+                        success: row.get(12)?,
+                        duration_ms: row.get(13)?,
+                        summary: row.get(14)?,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(events)
+    }
+
+    /// Fold events into the per-session, per-day rollups and delete them, in
+    /// one transaction. Returns the number of events deleted.
+    pub fn compact_events(&self, event_ids: &[i64]) -> Result<usize, rusqlite::Error> {
+        let ids = serde_json::to_string(event_ids).unwrap();
+        let mut conn = self.pool.write();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO event_rollups (project_dir, session_id, day, event_type, tool_name, source_agent,
+                                        event_count, failure_count, total_duration_ms, first_at, last_at)
+             SELECT project_dir, session_id, date(created_at), event_type, COALESCE(tool_name, ''), MAX(source_agent),
+                    COUNT(*), COALESCE(SUM(success = 0), 0), COALESCE(SUM(duration_ms), 0), MIN(created_at), MAX(created_at)
+             FROM events
+             WHERE id IN (SELECT value FROM json_each(?1))
+             GROUP BY project_dir, session_id, date(created_at), event_type, COALESCE(tool_name, '')
+             ON CONFLICT (project_dir, session_id, day, event_type, tool_name) DO UPDATE SET
+                 event_count = event_count + excluded.event_count,
+                 failure_count = failure_count + excluded.failure_count,
+                 total_duration_ms = total_duration_ms + excluded.total_duration_ms,
+                 first_at = MIN(first_at, excluded.first_at),
+                 last_at = MAX(last_at, excluded.last_at)",
+            [&ids],
+        )?;
+
+        let deleted = tx.execute(
+            "DELETE FROM events WHERE id IN (SELECT value FROM json_each(?1))",
+            [&ids],
+        )?;
+
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Rollups for a project, newest day first
+    pub fn get_event_rollups(&self, project_dir: &str, limit: i64) -> Result<Vec<EventRollup>, rusqlite::Error> {
+        let conn = self.pool.read();
+        let mut stmt = conn.prepare(
+            "SELECT project_dir, session_id, day, event_type, tool_name, source_agent,
+                    event_count, failure_count, total_duration_ms, first_at, last_at
+             FROM event_rollups WHERE project_dir = ?1
+             ORDER BY day DESC, session_id, event_type, tool_name LIMIT ?2",
+        )?;
+
+        let rollups = stmt
+            .query_map(params![project_dir, limit], |row| {
+                let tool_name: String = row.get(4)?;
+                Ok(EventRollup {
+                    project_dir: row.get(0)?,
+                    session_id: row.get(1)?,
+                    day: row.get(2)?,
+                    event_type: row.get(3)?,
+                    tool_name: (!tool_name.is_empty()).then_some(tool_name),
+                    source_agent: row.get(5)?,
+                    event_count: row.get(6)?,
+                    failure_count: row.get(7)?,
+                    total_duration_ms: row.get(8)?,
+                    first_at: row.get(9)?,
+                    last_at: row.get(10)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rollups)
+    }
+
+    /// Rebuild the database file to return space freed by deleted rows
+    pub fn vacuum(&self) -> Result<(), rusqlite::Error> {
+        let conn = self.pool.write();
+        conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
+    }
+
+    /// Get events without a feature_id (unlinked)
+    pub fn get_unlinked_events(&self, project_dir: Option<&str>, limit: i64) -> Result<Vec<AgentEvent>, rusqlite::Error> {
+        let conn = self.pool.read();
+
+        let (sql, params): (&str, Vec<Box<dyn rusqlite::ToSql>>) = if let Some(dir) = project_dir {
+            (
+                "SELECT id, event_type, source_agent, session_id, project_dir, tool_name, payload, feature_id, created_at, idempotency_key,
+                   tool_input, exit_code, success, duration_ms, summary
+                 FROM events WHERE (feature_id IS NULL OR feature_id = '') AND project_dir = ?1
+                 ORDER BY created_at DESC LIMIT ?2",
+                vec![Box::new(dir.to_string()), Box::new(limit)]
+            )
+        } else {
+            (
+                "SELECT id, event_type, source_agent, session_id, project_dir, tool_name, payload, feature_id, created_at, idempotency_key,
+                   tool_input, exit_code, success, duration_ms, summary
+                 FROM events WHERE (feature_id IS NULL OR feature_id = '')
+                 ORDER BY created_at DESC LIMIT ?1",
+                vec![Box::new(limit)]
+            )
+        };
+
+        let mut stmt = conn.prepare(sql)?;
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let events = stmt
+            .query_map(params_refs.as_slice(), |row| {
+                Ok(AgentEvent {
+                    id: Some(row.get(0)?),
+                    event_type: row.get(1)?,
+                    source_agent: row.get(2)?,
+                    session_id: row.get(3)?,
+                    project_dir: row.get(4)?,
+                    tool_name: row.get(5)?,
+                    payload: row.get(6)?,
+                    feature_id: row.get(7)?,
+                    created_at: row.get(8)?,
+                    idempotency_key: row.get(9)?,
+                    details: EventDetails {
+                        tool_input: row.get(10)?,
+                        exit_code: row.get(11)?,
+                        success: row.get(12)?,
+                        duration_ms: row.get(13)?,
+                        summary: row.get(14)?,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(events)
+    }
+
+    /// Update an event's feature_id
+    pub fn link_event_to_feature(&self, event_id: i64, feature_id: &str) -> Result<bool, rusqlite::Error> {
+        let conn = self.pool.write();
+        let rows = conn.execute(
+            "UPDATE events SET feature_id = ?1 WHERE id = ?2",
+            params![feature_id, event_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Replace a project's features with those read from its feature_list.json.
+    /// Changes to existing rows are recorded in `feature_history`.
+    pub fn sync_features(
+        &self,
+        project_dir: &str,
+        features: Vec<Feature>,
+    ) -> Result<(), rusqlite::Error> {
+        let mut conn = self.pool.write();
+        let tx = conn.transaction()?;
+        let changed_at = chrono::Utc::now().to_rfc3339();
+
+        for feature in features {
+            let steps_json = feature
+                .steps
+                .as_ref()
+                .map(|s| serde_json::to_string(s).unwrap_or_default());
+
+            let before = history_snapshot(&tx, &feature.id)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO features (
+                    id, project_dir, description, category, passes, in_progress, agent, steps,
+                    work_count, completion_criteria, updated_at,
+                    confidence, model, is_streaming, retry_count, token_cost, has_error, last_agent_update,
+                    manual_priority, human_override_until
+                 )
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'),
+                         ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+                params![
+                    feature.id,
+                    project_dir,
+                    feature.description,
+                    feature.category,
+                    feature.passes,
+                    feature.in_progress,
+                    feature.agent,
+                    steps_json,
+                    feature.work_count,
+                    feature.completion_criteria,
+                    feature.confidence,
+                    feature.model,
+                    feature.is_streaming,
+                    feature.retry_count,
+                    feature.token_cost,
+                    feature.has_error,
+                    feature.last_agent_update,
+                    feature.manual_priority,
+                    feature.human_override_until,
+                ],
+            )?;
+
+            // New rows have no history yet
+            if let Some(before) = before {
+                let after = history_snapshot(&tx, &feature.id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+                record_history(
+                    &tx,
+                    &feature.id,
+                    &before,
+                    &after,
+                    FILE_SYNC_SOURCE,
+                    &UpdateAuthor::default(),
+                    &changed_at,
+                )?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn get_features(&self, project_dir: Option<&str>) -> Result<Vec<Feature>, rusqlite::Error> {
+        let conn = self.pool.read();
+
+        fn parse_steps(steps_json: Option<String>) -> Option<Vec<String>> {
+            steps_json.and_then(|s| serde_json::from_str(&s).ok())
+        }
+
+        fn map_feature_row(row: &rusqlite::Row) -> rusqlite::Result<Feature> {
+            Ok(Feature {
+                id: row.get(0)?,
+                project_dir: row.get(1)?,
+                description: row.get(2)?,
+                category: row.get(3)?,
+                passes: row.get(4)?,
+                in_progress: row.get(5)?,
+                agent: row.get(6)?,
+                steps: parse_steps(row.get(7)?),
+                work_count: row.get::<_, Option<i32>>(8)?.unwrap_or(0),
+                completion_criteria: row.get(9)?,
+                updated_at: row.get(10)?,
+                confidence: row.get(11)?,
+                model: row.get(12)?,
+                is_streaming: row.get::<_, Option<bool>>(13)?.unwrap_or(false),
+                retry_count: row.get::<_, Option<i32>>(14)?.unwrap_or(0),
+                token_cost: row.get(15)?,
+                has_error: row.get::<_, Option<bool>>(16)?.unwrap_or(false),
+                last_agent_update: row.get(17)?,
+                manual_priority: row.get(18)?,
+                human_override_until: row.get(19)?,
+            })
+        }
+
+        let select_cols = "SELECT id, project_dir, description, category, passes, in_progress, agent, steps,
+                          work_count, completion_criteria, updated_at,
+                          confidence, model, is_streaming, retry_count, token_cost, has_error, last_agent_update,
+                          manual_priority, human_override_until
+                          FROM features";
+
+        if let Some(dir) = project_dir {
+            let mut stmt = conn.prepare(&format!("{} WHERE project_dir = ?1 ORDER BY id", select_cols))?;
+            let features = stmt
+                .query_map([dir], map_feature_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(features)
+        } else {
+            let mut stmt = conn.prepare(&format!("{} ORDER BY project_dir, id", select_cols))?;
+            let features = stmt
+                .query_map([], map_feature_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(features)
+        }
+    }
+
+    pub fn get_sessions(&self) -> Result<Vec<Session>, rusqlite::Error> {
+        let conn = self.pool.read();
+        let mut stmt = conn.prepare(
+            "SELECT session_id, source_agent, project_dir, started_at, last_activity, status
+             FROM sessions WHERE status = 'active' ORDER BY last_activity DESC",
+        )?;
+
+        let sessions = stmt
+            .query_map([], |row| {
+                Ok(Session {
+                    session_id: row.get(0)?,
+                    source_agent: row.get(1)?,
+                    project_dir: row.get(2)?,
+                    started_at: row.get(3)?,
+                    last_activity: row.get(4)?,
+                    status: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(sessions)
+    }
+
+    /// Get the most recently active sessions regardless of status
+    pub fn get_recent_sessions(&self, limit: i64) -> Result<Vec<Session>, rusqlite::Error> {
+        let conn = self.pool.read();
+        let mut stmt = conn.prepare(
+            "SELECT session_id, source_agent, project_dir, started_at, last_activity, status
+             FROM sessions ORDER BY last_activity DESC LIMIT ?1",
+        )?;
+
+        let sessions = stmt
+            .query_map([limit], |row| {
+                Ok(Session {
+                    session_id: row.get(0)?,
+                    source_agent: row.get(1)?,
+                    project_dir: row.get(2)?,
+                    started_at: row.get(3)?,
+                    last_activity: row.get(4)?,
+                    status: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(sessions)
+    }
+
+    pub fn upsert_session(&self, session: &Session) -> Result<(), rusqlite::Error> {
+        let conn = self.pool.write();
+        conn.execute(
+            "INSERT OR REPLACE INTO sessions (session_id, source_agent, project_dir, started_at, last_activity, status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                session.session_id,
+                session.source_agent,
+                session.project_dir,
+                session.started_at,
+                session.last_activity,
+                session.status,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn update_session_status(
+        &self,
+        session_id: &str,
+        status: &str,
+    ) -> Result<(), rusqlite::Error> {
+        let conn = self.pool.write();
+        conn.execute(
+            "UPDATE sessions SET status = ?1, last_activity = datetime('now') WHERE session_id = ?2",
+            params![status, session_id],
+        )?;
+        Ok(())
+    }
+
+    /// Clean up stale sessions that have been inactive for more than the specified minutes.
+    /// Returns the number of sessions marked as ended.
+    pub fn cleanup_stale_sessions(&self, inactive_minutes: i64) -> Result<usize, rusqlite::Error> {
+        let conn = self.pool.write();
+        let rows = conn.execute(
+            "UPDATE sessions SET status = 'ended'
+             WHERE status = 'active'
+             AND datetime(last_activity) < datetime('now', ?1)",
+            params![format!("-{} minutes", inactive_minutes)],
+        )?;
+
+        if rows > 0 {
+            tracing::info!("Cleaned up {} stale sessions (inactive > {} minutes)", rows, inactive_minutes);
+        }
+
+        Ok(rows)
+    }
+
+    /// Get feature and session statistics, optionally scoped to a single project
+    pub fn get_stats(&self, project_dir: Option<&str>) -> Result<Stats, rusqlite::Error> {
+        let conn = self.pool.read();
+
+        // A NULL project filter matches every row
+        let total: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM features WHERE ?1 IS NULL OR project_dir = ?1",
+            [project_dir],
+            |r| r.get(0),
+        )?;
+
+        let completed: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM features WHERE passes = 1 AND (?1 IS NULL OR project_dir = ?1)",
+            [project_dir],
+            |r| r.get(0),
+        )?;
+
+        let in_progress: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM features
+             WHERE in_progress = 1 AND passes = 0 AND (?1 IS NULL OR project_dir = ?1)",
+            [project_dir],
+            |r| r.get(0),
+        )?;
+
+        let active_sessions: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sessions WHERE status = 'active' AND (?1 IS NULL OR project_dir = ?1)",
+            [project_dir],
+            |r| r.get(0),
+        )?;
+
+        let percentage = if total > 0 {
+            (completed as f64 / total as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        Ok(Stats {
+            total,
+            completed,
+            in_progress,
+            percentage,
+            active_sessions,
+        })
+    }
+
+    pub fn get_config(&self) -> Result<Config, rusqlite::Error> {
+        read_config(&self.pool.read())
+    }
+
+    pub fn save_config(&self, config: &Config) -> Result<(), rusqlite::Error> {
+        write_config(&self.pool.write(), config)
+    }
+
+    pub fn get_projects(&self) -> Result<Vec<String>, rusqlite::Error> {
+        let conn = self.pool.read();
+        // Sessions register projects before any features exist for them
+        let mut stmt = conn.prepare(
+            "SELECT project_dir FROM features
+             UNION
+             SELECT project_dir FROM sessions
+             ORDER BY project_dir",
+        )?;
+
+        let projects = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+
+        Ok(projects)
+    }
+
+    /// Add a project to watched_projects if not already present.
+    /// Returns true if the project was added, false if already exists.
+    pub fn add_watched_project(&self, project_dir: &str) -> Result<bool, rusqlite::Error> {
+        // Read and write under the writer lock so concurrent adds aren't lost
+        let conn = self.pool.write();
+        let mut config = read_config(&conn)?;
+
+        if config.watched_projects.contains(&project_dir.to_string()) {
+            return Ok(false);
+        }
+
+        config.watched_projects.push(project_dir.to_string());
+        write_config(&conn, &config)?;
+        Ok(true)
+    }
+
+    /// Update a feature with source-aware override logic.
+    /// - Human updates always apply and lock the fields they set for the
+    ///   configured `HumanOverridePolicy`
+    /// - Agent updates are refused as a whole if any field they set is locked
+    ///
+    /// Every changed field is recorded in `feature_history`.
+    pub fn update_feature(
+        &self,
+        feature_id: &str,
+        update: FeatureUpdate,
+        source: UpdateSource,
+        author: &UpdateAuthor,
+    ) -> Result<UpdateOutcome, rusqlite::Error> {
+        let mut conn = self.pool.write();
+        let now = chrono::Utc::now();
+        let fields = update.fields();
+
+        if matches!(source, UpdateSource::Agent) {
+            let locked: Vec<String> = active_locks(&conn, feature_id, now)?
+                .into_iter()
+                .map(|lock| lock.field)
+                .filter(|field| fields.contains(&field.as_str()))
+                .collect();
+
+            if !locked.is_empty() {
+                tracing::info!(
+                    "Agent update blocked for feature {} - human override on {}",
+                    feature_id,
+                    locked.join(", ")
+                );
+                return Ok(UpdateOutcome::Locked(locked));
+            }
+        }
+
+        // Build dynamic UPDATE statement based on provided fields
+        let mut updates = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(v) = update.passes { updates.push("passes = ?"); params.push(Box::new(v)); }
+        if let Some(v) = update.in_progress { updates.push("in_progress = ?"); params.push(Box::new(v)); }
+        if let Some(v) = &update.agent { updates.push("agent = ?"); params.push(Box::new(v.clone())); }
+        if let Some(v) = update.confidence { updates.push("confidence = ?"); params.push(Box::new(v)); }
+        if let Some(v) = &update.model { updates.push("model = ?"); params.push(Box::new(v.clone())); }
+        if let Some(v) = update.is_streaming { updates.push("is_streaming = ?"); params.push(Box::new(v)); }
+        if let Some(v) = update.retry_count { updates.push("retry_count = ?"); params.push(Box::new(v)); }
+        if let Some(v) = update.token_cost { updates.push("token_cost = ?"); params.push(Box::new(v)); }
+        if let Some(v) = update.has_error { updates.push("has_error = ?"); params.push(Box::new(v)); }
+        if let Some(v) = &update.manual_priority { updates.push("manual_priority = ?"); params.push(Box::new(v.clone())); }
+
+        if updates.is_empty() {
+            return Ok(UpdateOutcome::Applied(Vec::new())); // Nothing to update
+        }
+
+        // Lock the fields a human set; None holds them until released
+        let lock_until = match source {
+            UpdateSource::Human => read_config(&conn)?
+                .human_override
+                .lock_minutes
+                .map(|minutes| (now + chrono::Duration::minutes(minutes.into())).to_rfc3339()),
+            UpdateSource::Agent => None,
+        };
+
+        // Add source-specific fields
+        match source {
+            UpdateSource::Human => {
+                // Latest timed expiry, shown as the board's lock badge
+                if let Some(until) = &lock_until {
+                    updates.push("human_override_until = ?");
+                    params.push(Box::new(until.clone()));
+                }
+            }
+            UpdateSource::Agent => {
+                updates.push("last_agent_update = ?");
+                params.push(Box::new(now.to_rfc3339()));
+            }
+        }
+
+        // Always update updated_at
+        updates.push("updated_at = datetime('now')");
+
+        // Build and execute query
+        let sql = format!(
+            "UPDATE features SET {} WHERE id = ?",
+            updates.join(", ")
+        );
+        params.push(Box::new(feature_id.to_string()));
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let tx = conn.transaction()?;
+        let Some(before) = history_snapshot(&tx, feature_id)? else {
+            return Ok(UpdateOutcome::NotFound);
+        };
+        tx.execute(&sql, params_refs.as_slice())?;
+        let after = history_snapshot(&tx, feature_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+        if matches!(source, UpdateSource::Human) {
+            for field in &fields {
+                tx.execute(
+                    "INSERT INTO feature_locks (feature_id, field, locked_until, locked_at)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT (feature_id, field) DO UPDATE SET
+                         locked_until = excluded.locked_until,
+                         locked_at = excluded.locked_at",
+                    params![feature_id, field, lock_until, now.to_rfc3339()],
+                )?;
+            }
+        }
+
+        let changes = record_history(
+            &tx,
+            feature_id,
+            &before,
+            &after,
+            source.as_str(),
+            author,
+            &now.to_rfc3339(),
+        )?;
+        tx.commit()?;
+
+        Ok(UpdateOutcome::Applied(changes))
+    }
+
+    /// The feature's fields currently locked by a human edit
+    pub fn get_feature_locks(&self, feature_id: &str) -> Result<Vec<FieldLock>, rusqlite::Error> {
+        let conn = self.pool.read();
+        active_locks(&conn, feature_id, chrono::Utc::now())
+    }
+
+    /// Release human locks on a feature: the given fields, or all of them.
+    /// Returns the number of locks removed.
+    pub fn release_override(&self, feature_id: &str, fields: Option<&[String]>) -> Result<usize, rusqlite::Error> {
+        let mut conn = self.pool.write();
+        let tx = conn.transaction()?;
+
+        let released = match fields {
+            Some(fields) => {
+                let fields = serde_json::to_string(fields).unwrap();
+                tx.execute(
+                    "DELETE FROM feature_locks
+                     WHERE feature_id = ?1 AND field IN (SELECT value FROM json_each(?2))",
+                    params![feature_id, fields],
+                )?
+            }
+            None => tx.execute("DELETE FROM feature_locks WHERE feature_id = ?1", [feature_id])?,
+        };
+
+        tx.execute(
+            "UPDATE features SET human_override_until = NULL
+             WHERE id = ?1 AND NOT EXISTS (SELECT 1 FROM feature_locks WHERE feature_id = ?1)",
+            [feature_id],
+        )?;
+        tx.commit()?;
+
+        Ok(released)
+    }
+
+    /// Where events and features stand now; see `ChangeMarker`
+    pub fn get_change_marker(&self) -> Result<ChangeMarker, rusqlite::Error> {
+        let conn = self.pool.read();
+        conn.query_row(
+            "SELECT (SELECT coalesce(max(id), 0) FROM events),
+                    (SELECT count(*) || '/' || coalesce(max(updated_at), '') FROM features)
+                    || '/' || (SELECT coalesce(max(id), 0) FROM feature_history)
+                    || '/' || (SELECT count(*) || '/' || coalesce(max(locked_at), '') FROM feature_locks)",
+            [],
+            |row| {
+                Ok(ChangeMarker {
+                    last_event_id: row.get(0)?,
+                    features: row.get(1)?,
+                })
+            },
+        )
+    }
+
+    /// A feature's recorded changes, newest first
+    pub fn get_feature_history(&self, feature_id: &str, limit: i64) -> Result<Vec<FeatureChange>, rusqlite::Error> {
+        let conn = self.pool.read();
+        let mut stmt = conn.prepare(
+            "SELECT id, feature_id, field, old_value, new_value, source, agent, session_id, changed_at
+             FROM feature_history WHERE feature_id = ?1
+             ORDER BY id DESC LIMIT ?2",
+        )?;
+
+        let changes = stmt
+            .query_map(params![feature_id, limit], |row| {
+                Ok(FeatureChange {
+                    id: row.get(0)?,
+                    feature_id: row.get(1)?,
+                    field: row.get(2)?,
+                    old_value: row.get(3)?,
+                    new_value: row.get(4)?,
+                    source: row.get(5)?,
+                    agent: row.get(6)?,
+                    session_id: row.get(7)?,
+                    changed_at: row.get(8)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(changes)
+    }
+
+    /// Delete a feature from the cache. Agents cannot delete a feature with
+    /// any field locked by a human. Returns false if the feature does not
+    /// exist or an agent delete was blocked by a human override.
+    pub fn delete_feature(&self, feature_id: &str, source: UpdateSource) -> Result<bool, rusqlite::Error> {
+        let conn = self.pool.write();
+
+        if matches!(source, UpdateSource::Agent) {
+            let locks = active_locks(&conn, feature_id, chrono::Utc::now())?;
+            if !locks.is_empty() {
+                tracing::info!("Agent delete blocked for feature {} - human override active", feature_id);
+                return Ok(false);
+            }
+        }
+
+        let rows = conn.execute("DELETE FROM features WHERE id = ?1", [feature_id])?;
+        conn.execute("DELETE FROM feature_locks WHERE feature_id = ?1", [feature_id])?;
+        Ok(rows > 0)
+    }
+
+    /// Sync a feature from graph database to SQLite cache.
+    /// This upserts the feature, converting graph status to SQLite boolean flags.
+    /// Returns true if the cached row was inserted or actually changed.
+    pub fn sync_feature_from_graph(&self, feature: &GraphFeatureSync) -> Result<bool, rusqlite::Error> {
+        let mut conn = self.pool.write();
+        let tx = conn.transaction()?;
+
+        let mut passes = feature.status == "complete";
+        let mut in_progress = feature.status == "in_progress";
+        let steps_json = serde_json::to_string(&feature.steps).ok();
+
+        let mut graph_updated_at = Some(feature.graph_updated_at);
+
+        // Keep the cached status while a human holds it. The row keeps its old
+        // graph_updated_at so the sync holds its watermark and pulls the graph's
+        // status again once the lock is gone.
+        let current: Option<(bool, bool, Option<i64>)> = tx
+            .query_row(
+                "SELECT passes, in_progress, graph_updated_at FROM features WHERE id = ?1",
+                [&feature.id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+
+        if let Some((cached_passes, cached_in_progress, cached_graph_updated_at)) = current {
+            let held = (passes, in_progress) != (cached_passes, cached_in_progress)
+                && active_locks(&tx, &feature.id, chrono::Utc::now())?
+                    .iter()
+                    .any(|lock| lock.field == "status");
+            if held {
+                passes = cached_passes;
+                in_progress = cached_in_progress;
+                graph_updated_at = cached_graph_updated_at;
+            }
+        }
+
+        let before = history_snapshot(&tx, &feature.id)?;
+
+        // The WHERE clause skips no-op updates so unchanged rows report 0 changes
+        let rows = tx.execute(
+            "INSERT INTO features (id, project_dir, description, category, passes, in_progress, steps, updated_at, from_graph, graph_updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'), 1, ?8)
+             ON CONFLICT(id) DO UPDATE SET
+                project_dir = excluded.project_dir,
+                description = excluded.description,
+                category = excluded.category,
+                passes = excluded.passes,
+                in_progress = excluded.in_progress,
+                steps = excluded.steps,
+                updated_at = datetime('now'),
+                from_graph = 1,
+                graph_updated_at = excluded.graph_updated_at
+             WHERE features.from_graph IS NOT 1
+                OR features.project_dir IS NOT excluded.project_dir
+                OR features.description IS NOT excluded.description
+                OR features.category IS NOT excluded.category
+                OR features.passes IS NOT excluded.passes
+                OR features.in_progress IS NOT excluded.in_progress
+                OR features.steps IS NOT excluded.steps
+                OR features.graph_updated_at IS NOT excluded.graph_updated_at",
+            params![
+                feature.id,
+                feature.project_dir,
+                feature.description,
+                feature.category,
+                passes,
+                in_progress,
+                steps_json,
+                graph_updated_at,
+            ],
+        )?;
+
+        // Record moves made on the graph side; new rows have no history yet
+        if let (true, Some(before)) = (rows > 0, before) {
+            let after = history_snapshot(&tx, &feature.id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+            record_history(
+                &tx,
+                &feature.id,
+                &before,
+                &after,
+                GRAPH_SYNC_SOURCE,
+                &UpdateAuthor::default(),
+                &chrono::Utc::now().to_rfc3339(),
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(rows > 0)
+    }
+
+    /// Get the graph `updated_at` last synced for a feature (microseconds since
+    /// epoch, 0 if unknown). Returns None for features not mirrored from the graph.
+    pub fn get_graph_updated_at(&self, feature_id: &str) -> Result<Option<i64>, rusqlite::Error> {
+        let conn = self.pool.read();
+        conn.query_row(
+            "SELECT COALESCE(graph_updated_at, 0) FROM features WHERE id = ?1 AND from_graph = 1",
+            [feature_id],
+            |row| row.get(0),
+        )
+        .optional()
+    }
+
+    /// Record the graph `updated_at` after pushing a change to the graph
+    pub fn set_graph_updated_at(&self, feature_id: &str, graph_updated_at: i64) -> Result<(), rusqlite::Error> {
+        let conn = self.pool.write();
+        conn.execute(
+            "UPDATE features SET graph_updated_at = ?1 WHERE id = ?2",
+            params![graph_updated_at, feature_id],
+        )?;
+        Ok(())
+    }
+
+    /// Reconcile graph-mirrored cache rows against the full graph membership
+    /// (feature id -> project path). Rows whose feature no longer exists in the
+    /// graph are deleted and rows whose feature moved project are re-homed.
+    pub fn reconcile_graph_features(
+        &self,
+        graph_memberships: &HashMap<String, String>,
+    ) -> Result<GraphReconcileResult, rusqlite::Error> {
+        let mut conn = self.pool.write();
+        let tx = conn.transaction()?;
+
+        let cached: Vec<(String, String)> = {
+            let mut stmt = tx.prepare("SELECT id, project_dir FROM features WHERE from_graph = 1")?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+
+        let mut result = GraphReconcileResult::default();
+
+        for (id, project_dir) in cached {
+            match graph_memberships.get(&id) {
+                None => {
+                    tx.execute("DELETE FROM features WHERE id = ?1", [&id])?;
+                    result.deleted.push(id);
+                }
+                Some(graph_project) if *graph_project != project_dir => {
+                    tx.execute(
+                        "UPDATE features SET project_dir = ?1, updated_at = datetime('now') WHERE id = ?2",
+                        params![graph_project, id],
+                    )?;
+                    result.moved.push(id);
+                }
+                Some(_) => {}
+            }
+        }
+
+        tx.commit()?;
+
+        if !result.deleted.is_empty() || !result.moved.is_empty() {
+            tracing::info!(
+                "Reconciled graph cache: {} deleted, {} moved",
+                result.deleted.len(),
+                result.moved.len()
+            );
+        }
+
+        Ok(result)
+    }
+
+    /// Get the graph sync watermark (microseconds since epoch), 0 if never synced
+    pub fn get_graph_sync_watermark(&self) -> Result<i64, rusqlite::Error> {
+        let conn = self.pool.read();
+        let value: Option<String> = conn
+            .query_row(
+                "SELECT value FROM config WHERE key = 'graph_sync_watermark'",
+                [],
+                |r| r.get(0),
+            )
+            .ok();
+
+        Ok(value.and_then(|v| v.parse().ok()).unwrap_or(0))
+    }
+
+    /// Store the graph sync watermark after a successful sync
+    pub fn set_graph_sync_watermark(&self, watermark: i64) -> Result<(), rusqlite::Error> {
+        let conn = self.pool.write();
+        conn.execute(
+            "INSERT OR REPLACE INTO config (key, value) VALUES ('graph_sync_watermark', ?1)",
+            [watermark.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Get a single feature by ID
+    pub fn get_feature(&self, feature_id: &str) -> Result<Option<Feature>, rusqlite::Error> {
+        let conn = self.pool.read();
+
+        fn parse_steps(steps_json: Option<String>) -> Option<Vec<String>> {
+            steps_json.and_then(|s| serde_json::from_str(&s).ok())
+        }
+
+        let result = conn.query_row(
+            "SELECT id, project_dir, description, category, passes, in_progress, agent, steps,
+                    work_count, completion_criteria, updated_at,
+                    confidence, model, is_streaming, retry_count, token_cost, has_error, last_agent_update,
+                    manual_priority, human_override_until
+             FROM features WHERE id = ?1",
+            [feature_id],
+            |row| {
+                Ok(Feature {
+                    id: row.get(0)?,
+                    project_dir: row.get(1)?,
+                    description: row.get(2)?,
+                    category: row.get(3)?,
+                    passes: row.get(4)?,
+                    in_progress: row.get(5)?,
+                    agent: row.get(6)?,
+                    steps: parse_steps(row.get(7)?),
+                    work_count: row.get::<_, Option<i32>>(8)?.unwrap_or(0),
+                    completion_criteria: row.get(9)?,
+                    updated_at: row.get(10)?,
+                    confidence: row.get(11)?,
+                    model: row.get(12)?,
+                    is_streaming: row.get::<_, Option<bool>>(13)?.unwrap_or(false),
+                    retry_count: row.get::<_, Option<i32>>(14)?.unwrap_or(0),
+                    token_cost: row.get(15)?,
+                    has_error: row.get::<_, Option<bool>>(16)?.unwrap_or(false),
+                    last_agent_update: row.get(17)?,
+                    manual_priority: row.get(18)?,
+                    human_override_until: row.get(19)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(feature) => Ok(Some(feature)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // =========================================================================
+    // GRAPH OUTBOX
+    // =========================================================================
+
+    /// Queue a serialized graph mutation for replay once the graph is reachable
+    pub fn enqueue_outbox(&self, operation: &str, payload: &str) -> Result<i64, rusqlite::Error> {
+        let conn = self.pool.write();
+        conn.execute(
+            "INSERT INTO graph_outbox (operation, payload) VALUES (?1, ?2)",
+            params![operation, payload],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Get pending outbox entries in the order they were queued
+    pub fn get_pending_outbox(&self, limit: i64) -> Result<Vec<OutboxEntry>, rusqlite::Error> {
+        let conn = self.pool.read();
+        let mut stmt = conn.prepare(
+            "SELECT id, operation, payload, attempts
+             FROM graph_outbox WHERE status = 'pending' ORDER BY id LIMIT ?1",
+        )?;
+
+        let entries = stmt
+            .query_map([limit], |row| {
+                Ok(OutboxEntry {
+                    id: row.get(0)?,
+                    operation: row.get(1)?,
+                    payload: row.get(2)?,
+                    attempts: row.get::<_, Option<i32>>(3)?.unwrap_or(0),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    /// Count outbox entries still waiting to be replayed
+    pub fn count_pending_outbox(&self) -> Result<i64, rusqlite::Error> {
+        let conn = self.pool.read();
+        conn.query_row(
+            "SELECT COUNT(*) FROM graph_outbox WHERE status = 'pending'",
+            [],
+            |r| r.get(0),
+        )
+    }
+
+    /// Remove an outbox entry after it has been applied to the graph
+    pub fn remove_outbox_entry(&self, id: i64) -> Result<(), rusqlite::Error> {
+        let conn = self.pool.write();
+        conn.execute("DELETE FROM graph_outbox WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// Record a failed replay attempt. Entries that reach `max_attempts` are
+    /// marked 'failed' so they no longer block the rest of the queue.
+    /// Returns true if the entry was given up on.
+    pub fn record_outbox_failure(
+        &self,
+        id: i64,
+        error: &str,
+        max_attempts: i32,
+    ) -> Result<bool, rusqlite::Error> {
+        let conn = self.pool.write();
+        conn.execute(
+            "UPDATE graph_outbox
+             SET attempts = attempts + 1,
+                 last_error = ?1,
+                 status = CASE WHEN attempts + 1 >= ?2 THEN 'failed' ELSE status END
+             WHERE id = ?3",
+            params![error, max_attempts, id],
+        )?;
+
+        let status: String = conn.query_row(
+            "SELECT status FROM graph_outbox WHERE id = ?1",
+            [id],
+            |r| r.get(0),
+        )?;
+        Ok(status == "failed")
+    }
+}
+
+/// A graph mutation queued in the SQLite outbox
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub operation: String,
+    pub payload: String,
+    pub attempts: i32,
+}
+
+fn read_config(conn: &Connection) -> Result<Config, rusqlite::Error> {
+    let config_json: Option<String> = conn
+        .query_row("SELECT value FROM config WHERE key = 'main'", [], |r| {
+            r.get(0)
+        })
+        .ok();
+
+    match config_json {
+        Some(json) => Ok(serde_json::from_str(&json).unwrap_or_default()),
+        None => Ok(Config::default()),
+    }
+}
+
+fn write_config(conn: &Connection, config: &Config) -> Result<(), rusqlite::Error> {
+    let json = serde_json::to_string(config).unwrap();
+    conn.execute(
+        "INSERT OR REPLACE INTO config (key, value) VALUES ('main', ?1)",
+        [json],
+    )?;
+    Ok(())
+}
+
+/// Turn free text into an FTS5 query. Each term is quoted as a phrase, so
+/// punctuation such as `auth.rs` or `--release` needs no escaping; terms
+/// without any letters or digits are dropped.
+fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .filter_map(|term| {
+            let (term, prefix) = match term.strip_suffix('*') {
+                Some(stem) => (stem, "*"),
+                None => (term, ""),
+            };
+            term.chars()
+                .any(char::is_alphanumeric)
+                .then(|| format!("\"{}\"{}", term.replace('"', "\"\""), prefix))
+        })
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Parse a search time bound (RFC 3339 or `YYYY-MM-DD`) into the SQLite
+/// datetime format used for comparisons. A bare date used as an upper bound
+/// covers the whole day.
+pub fn parse_search_time(value: &str, end_of_day: bool) -> Option<String> {
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&chrono::Utc).format(FORMAT).to_string());
+    }
+
+    let date = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    }?;
+    Some(time.format(FORMAT).to_string())
+}
+
+/// `feature_history` source for changes pulled in by graph sync
+pub const GRAPH_SYNC_SOURCE: &str = "graph";
+
+/// `feature_history` source for changes read from a project's feature_list.json
+pub const FILE_SYNC_SOURCE: &str = "file";
+
+/// Feature fields tracked in `feature_history`, each with the SQL expression
+/// that renders it as text. `status` stands in for passes/in_progress.
+const HISTORY_FIELDS: &[(&str, &str)] = &[
+    ("status", "CASE WHEN passes THEN 'complete' WHEN in_progress THEN 'in_progress' ELSE 'pending' END"),
+    ("agent", "agent"),
+    ("confidence", "CAST(confidence AS TEXT)"),
+    ("model", "model"),
+    ("is_streaming", "CASE WHEN is_streaming IS NULL THEN NULL WHEN is_streaming THEN 'true' ELSE 'false' END"),
+    ("retry_count", "CAST(retry_count AS TEXT)"),
+    ("token_cost", "CAST(token_cost AS TEXT)"),
+    ("has_error", "CASE WHEN has_error IS NULL THEN NULL WHEN has_error THEN 'true' ELSE 'false' END"),
+    ("manual_priority", "manual_priority"),
+];
+
+/// The tracked fields of a feature, in `HISTORY_FIELDS` order
+fn history_snapshot(conn: &Connection, feature_id: &str) -> Result<Option<Vec<Option<String>>>, rusqlite::Error> {
+    let columns: Vec<&str> = HISTORY_FIELDS.iter().map(|(_, expr)| *expr).collect();
+    let sql = format!("SELECT {} FROM features WHERE id = ?1", columns.join(", "));
+    conn.query_row(&sql, [feature_id], |row| {
+        (0..HISTORY_FIELDS.len()).map(|i| row.get(i)).collect()
+    })
+    .optional()
+}
+
+/// Write a history row for every field that differs between two snapshots
+fn record_history(
+    conn: &Connection,
+    feature_id: &str,
+    before: &[Option<String>],
+    after: &[Option<String>],
+    source: &str,
+    author: &UpdateAuthor,
+    changed_at: &str,
+) -> Result<Vec<FeatureChange>, rusqlite::Error> {
+    let mut changes = Vec::new();
+
+    for ((field, _), (old_value, new_value)) in HISTORY_FIELDS.iter().zip(before.iter().zip(after)) {
+        if old_value == new_value {
+            continue;
+        }
+
+        conn.execute(
+            "INSERT INTO feature_history (feature_id, field, old_value, new_value, source, agent, session_id, changed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                feature_id,
+                field,
+                old_value,
+                new_value,
+                source,
+                author.agent,
+                author.session_id,
+                changed_at,
+            ],
+        )?;
+
+        changes.push(FeatureChange {
+            id: conn.last_insert_rowid(),
+            feature_id: feature_id.to_string(),
+            field: field.to_string(),
+            old_value: old_value.clone(),
+            new_value: new_value.clone(),
+            source: source.to_string(),
+            agent: author.agent.clone(),
+            session_id: author.session_id.clone(),
+            changed_at: changed_at.to_string(),
+        });
+    }
+
+    Ok(changes)
+}
+
+/// The feature's locks that have not expired at `now`
+fn active_locks(
+    conn: &Connection,
+    feature_id: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<FieldLock>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT feature_id, field, locked_until, locked_at FROM feature_locks
+         WHERE feature_id = ?1 ORDER BY field",
+    )?;
+
+    let locks = stmt
+        .query_map([feature_id], |row| {
+            Ok(FieldLock {
+                feature_id: row.get(0)?,
+                field: row.get(1)?,
+                locked_until: row.get(2)?,
+                locked_at: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(locks
+        .into_iter()
+        .filter(|lock| match &lock.locked_until {
+            None => true,
+            Some(until) => chrono::DateTime::parse_from_rfc3339(until)
+                .map(|until| until > now)
+                .unwrap_or(false),
+        })
+        .collect())
+}
+
+/// Source of a feature update - determines override behavior
+#[derive(Debug, Clone, Copy)]
+pub enum UpdateSource {
+    Human, // User interaction (drag-drop, click, etc.) - always wins, locks the fields it sets
+    Agent, // Agent/hook update - blocked if it sets a locked field
+}
+
+/// Result of `Database::update_feature`
+#[derive(Debug, Clone)]
+pub enum UpdateOutcome {
+    /// Applied; the field changes it recorded
+    Applied(Vec<FeatureChange>),
+    /// An agent update was refused because these fields are locked by a human
+    Locked(Vec<String>),
+    NotFound,
+}
+
+impl UpdateSource {
+    /// Name recorded in `feature_history`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpdateSource::Human => "human",
+            UpdateSource::Agent => "agent",
+        }
+    }
+}
+
+/// Who made a feature update, recorded alongside each change
+#[derive(Debug, Clone, Default)]
+pub struct UpdateAuthor {
+    pub agent: Option<String>,
+    pub session_id: Option<String>,
+}
+
+/// Feature data from graph database for syncing to SQLite cache
+#[derive(Debug, Clone)]
+pub struct GraphFeatureSync {
+    pub id: String,
+    pub project_dir: String,
+    pub description: String,
+    pub category: String,
+    pub status: String,
+    pub steps: Vec<String>,
+    /// Graph `updated_at` in microseconds since epoch
+    pub graph_updated_at: i64,
+}
+
+/// Cache rows changed by a graph reconciliation pass
+#[derive(Debug, Clone, Default)]
+pub struct GraphReconcileResult {
+    pub deleted: Vec<String>,
+    pub moved: Vec<String>,
+}
+
+/// Partial update struct for features - only set fields you want to update
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureUpdate {
+    pub passes: Option<bool>,
+    pub in_progress: Option<bool>,
+    pub agent: Option<String>,
+    pub confidence: Option<i32>,
+    pub model: Option<String>,
+    pub is_streaming: Option<bool>,
+    pub retry_count: Option<i32>,
+    pub token_cost: Option<i64>,
+    pub has_error: Option<bool>,
+    pub manual_priority: Option<String>,
+}
+
+impl FeatureUpdate {
+    /// Names of the fields this update sets, as used for locks and history.
+    /// passes and in_progress together are "status".
+    pub fn fields(&self) -> Vec<&'static str> {
+        let set = [
+            ("status", self.passes.is_some() || self.in_progress.is_some()),
+            ("agent", self.agent.is_some()),
+            ("confidence", self.confidence.is_some()),
+            ("model", self.model.is_some()),
+            ("is_streaming", self.is_streaming.is_some()),
+            ("retry_count", self.retry_count.is_some()),
+            ("token_cost", self.token_cost.is_some()),
+            ("has_error", self.has_error.is_some()),
+            ("manual_priority", self.manual_priority.is_some()),
+        ];
+        set.into_iter().filter(|(_, is_set)| *is_set).map(|(field, _)| field).collect()
+    }
+}
+
+/// Hand-built rows shared by the crate's unit tests
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::{AgentEvent, EventDetails, GraphFeatureSync};
+
+    /// A PostToolUse event from claude-code in session "s1"
+    pub(crate) fn event(tool: &str, project_dir: &str, payload: &str) -> AgentEvent {
+        AgentEvent {
+            id: None,
+            event_type: "PostToolUse".to_string(),
+            source_agent: "claude-code".to_string(),
+            session_id: "s1".to_string(),
+            project_dir: project_dir.to_string(),
+            tool_name: Some(tool.to_string()),
+            payload: Some(payload.to_string()),
+            feature_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            idempotency_key: None,
+            details: EventDetails::default(),
+        }
+    }
+
+    /// A pending feature as the graph sync hands it to the cache
+    pub(crate) fn graph_feature(id: &str, project_dir: &str) -> GraphFeatureSync {
+        GraphFeatureSync {
+            id: id.to_string(),
+            project_dir: project_dir.to_string(),
+            description: format!("Feature {}", id),
+            category: "functional".to_string(),
+            status: "pending".to_string(),
+            steps: vec!["Write the login form".to_string()],
+            graph_updated_at: 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::{event, graph_feature};
+    use super::*;
+
+    fn feature(id: &str, description: &str) -> GraphFeatureSync {
+        GraphFeatureSync {
+            description: description.to_string(),
+            ..graph_feature(id, "/a")
+        }
+    }
+
+    fn applied(outcome: UpdateOutcome) -> Vec<FeatureChange> {
+        match outcome {
+            UpdateOutcome::Applied(changes) => changes,
+            other => panic!("update not applied: {:?}", other),
+        }
+    }
+
+    fn search(db: &Database, text: &str, project_dir: Option<&str>) -> SearchResults {
+        db.search(&SearchQuery {
+            text: text.to_string(),
+            project_dir: project_dir.map(str::to_string),
+            limit: 10,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fts_query_quotes_terms() {
+        assert_eq!(fts_query("cargo test").as_deref(), Some(r#""cargo" "test""#));
+        assert_eq!(fts_query(r#"auth.rs say"hi" cfg*"#).as_deref(), Some(r#""auth.rs" "say""hi""" "cfg"*"#));
+        assert_eq!(fts_query("  -- * "), None);
+    }
+
+    #[test]
+    fn test_search_events_by_payload() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        db.insert_event(&event("Bash", "/a", r#"{"command":"cargo test --lib auth.rs"}"#)).unwrap();
+        db.insert_event(&event("Read", "/b", r#"{"file_path":"src/auth.rs"}"#)).unwrap();
+
+        let hits = search(&db, "cargo auth.rs", None).events;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, SearchHitKind::Event);
+        assert_eq!(hits[0].title, "Bash");
+        assert!(hits[0].snippet.contains("<mark>cargo</mark>"), "{}", hits[0].snippet);
+
+        assert_eq!(search(&db, "auth.rs", None).events.len(), 2);
+        assert_eq!(search(&db, "auth.rs", Some("/b")).events.len(), 1);
+        assert_eq!(search(&db, "carg*", None).events.len(), 1);
+        assert!(search(&db, "auth.rs", None).features.is_empty());
+    }
+
+    #[test]
+    fn test_search_time_range() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        db.insert_event(&event("Bash", "/a", "cargo build")).unwrap();
+
+        let query = |from: &str, to: &str| SearchQuery {
+            text: "cargo".to_string(),
+            from: parse_search_time(from, false),
+            to: parse_search_time(to, true),
+            limit: 10,
+            ..Default::default()
+        };
+        // Stored events are stamped with the insert time
+        let today = chrono::Utc::now().date_naive();
+        let day = |offset: i64| (today + chrono::Duration::days(offset)).to_string();
+        let hour_ahead = (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+
+        assert_eq!(db.search(&query(&day(0), &day(0))).unwrap().events.len(), 1);
+        assert_eq!(db.search(&query(&hour_ahead, &day(2))).unwrap().events.len(), 0);
+        assert_eq!(db.search(&query(&day(-2), &day(-1))).unwrap().events.len(), 0);
+        assert_eq!(parse_search_time("yesterday", false), None);
+    }
+
+    #[test]
+    fn test_feature_index_follows_changes() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        db.sync_feature_from_graph(&feature("f1", "User authentication")).unwrap();
+
+        let hits = search(&db, "login", None).features;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, SearchHitKind::Feature);
+        assert_eq!(hits[0].id, "f1");
+
+        let mut renamed = feature("f1", "Password reset");
+        renamed.steps = vec![];
+        renamed.graph_updated_at = 2;
+        db.sync_feature_from_graph(&renamed).unwrap();
+        assert!(search(&db, "authentication", None).features.is_empty());
+        assert_eq!(search(&db, "password", None).features.len(), 1);
+
+        db.delete_feature("f1", UpdateSource::Human).unwrap();
+        assert!(search(&db, "password", None).features.is_empty());
+    }
+
+    #[test]
+    fn test_update_records_history() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        db.sync_feature_from_graph(&feature("f1", "User authentication")).unwrap();
+
+        let author = UpdateAuthor {
+            agent: Some("claude-code".to_string()),
+            session_id: Some("s1".to_string()),
+        };
+        let update = FeatureUpdate {
+            passes: Some(false),
+            in_progress: Some(true),
+            confidence: Some(80),
+            ..Default::default()
+        };
+        let changes = applied(db.update_feature("f1", update, UpdateSource::Agent, &author).unwrap());
+
+        let fields: Vec<_> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, ["status", "confidence"]);
+        assert_eq!(changes[0].old_value.as_deref(), Some("pending"));
+        assert_eq!(changes[0].new_value.as_deref(), Some("in_progress"));
+        assert_eq!(changes[1].old_value, None);
+        assert_eq!(changes[1].new_value.as_deref(), Some("80"));
+
+        // Re-applying the same values records nothing
+        let update = FeatureUpdate {
+            in_progress: Some(true),
+            ..Default::default()
+        };
+        let changes = db.update_feature("f1", update, UpdateSource::Human, &UpdateAuthor::default()).unwrap();
+        assert!(applied(changes).is_empty());
+
+        // Blocked agent updates and unknown features are not recorded
+        let update = FeatureUpdate {
+            passes: Some(true),
+            ..Default::default()
+        };
+        assert!(matches!(
+            db.update_feature("f1", update.clone(), UpdateSource::Agent, &author).unwrap(),
+            UpdateOutcome::Locked(_)
+        ));
+        assert!(matches!(
+            db.update_feature("f2", update, UpdateSource::Human, &author).unwrap(),
+            UpdateOutcome::NotFound
+        ));
+
+        let history = db.get_feature_history("f1", 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].field, "confidence");
+        assert_eq!(history[1].source, "agent");
+        assert_eq!(history[1].agent.as_deref(), Some("claude-code"));
+        assert_eq!(history[1].session_id.as_deref(), Some("s1"));
+    }
+
+    #[test]
+    fn test_graph_sync_records_status_moves() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        db.sync_feature_from_graph(&feature("f1", "User authentication")).unwrap();
+        assert!(db.get_feature_history("f1", 10).unwrap().is_empty());
+
+        let mut completed = feature("f1", "User authentication");
+        completed.status = "complete".to_string();
+        completed.graph_updated_at = 2;
+        db.sync_feature_from_graph(&completed).unwrap();
+
+        let history = db.get_feature_history("f1", 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].source, GRAPH_SYNC_SOURCE);
+        assert_eq!(
+            (history[0].old_value.as_deref(), history[0].new_value.as_deref()),
+            (Some("pending"), Some("complete"))
+        );
+
+        // History outlives the feature
+        db.delete_feature("f1", UpdateSource::Human).unwrap();
+        assert_eq!(db.get_feature_history("f1", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_human_locks_cover_only_touched_fields() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        db.sync_feature_from_graph(&feature("f1", "User authentication")).unwrap();
+        let author = UpdateAuthor::default();
+
+        let update = FeatureUpdate {
+            manual_priority: Some("high".to_string()),
+            ..Default::default()
+        };
+        applied(db.update_feature("f1", update, UpdateSource::Human, &author).unwrap());
+        let locks = db.get_feature_locks("f1").unwrap();
+        assert_eq!(locks.len(), 1);
+        assert_eq!(locks[0].field, "manual_priority");
+        assert!(locks[0].locked_until.is_some());
+
+        // Fields the human did not touch stay open to agents
+        let update = FeatureUpdate {
+            token_cost: Some(1200),
+            is_streaming: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(applied(db.update_feature("f1", update, UpdateSource::Agent, &author).unwrap()).len(), 2);
+
+        // ...but an update setting a locked field is refused as a whole
+        let update = FeatureUpdate {
+            token_cost: Some(1500),
+            manual_priority: Some("low".to_string()),
+            ..Default::default()
+        };
+        match db.update_feature("f1", update, UpdateSource::Agent, &author).unwrap() {
+            UpdateOutcome::Locked(fields) => assert_eq!(fields, ["manual_priority"]),
+            other => panic!("expected a lock, got {:?}", other),
+        }
+        assert_eq!(db.get_feature("f1").unwrap().unwrap().token_cost, Some(1200));
+        assert!(!db.delete_feature("f1", UpdateSource::Agent).unwrap());
+    }
+
+    #[test]
+    fn test_locks_until_released() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        db.sync_feature_from_graph(&feature("f1", "User authentication")).unwrap();
+        let author = UpdateAuthor::default();
+
+        let mut config = db.get_config().unwrap();
+        config.human_override.lock_minutes = None;
+        db.save_config(&config).unwrap();
+
+        let update = FeatureUpdate {
+            passes: Some(true),
+            has_error: Some(false),
+            ..Default::default()
+        };
+        applied(db.update_feature("f1", update, UpdateSource::Human, &author).unwrap());
+        let locks = db.get_feature_locks("f1").unwrap();
+        let fields: Vec<_> = locks.iter().map(|l| l.field.as_str()).collect();
+        assert_eq!(fields, ["has_error", "status"]);
+        assert!(locks.iter().all(|l| l.locked_until.is_none()));
+
+        // A graph sync cannot move a locked status
+        let mut pending = feature("f1", "User authentication");
+        pending.graph_updated_at = 2;
+        db.sync_feature_from_graph(&pending).unwrap();
+        assert!(db.get_feature("f1").unwrap().unwrap().passes);
+
+        assert_eq!(db.release_override("f1", Some(&["status".to_string()])).unwrap(), 1);
+        let update = FeatureUpdate {
+            in_progress: Some(true),
+            ..Default::default()
+        };
+        applied(db.update_feature("f1", update, UpdateSource::Agent, &author).unwrap());
+
+        assert_eq!(db.release_override("f1", None).unwrap(), 1);
+        assert!(db.get_feature_locks("f1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_held_status_is_pulled_after_lock_expires() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        db.sync_feature_from_graph(&feature("f1", "User authentication")).unwrap();
+
+        let update = FeatureUpdate {
+            passes: Some(true),
+            ..Default::default()
+        };
+        applied(db.update_feature("f1", update, UpdateSource::Human, &UpdateAuthor::default()).unwrap());
+
+        // The graph moves the status while the lock is held: keep ours and
+        // leave graph_updated_at behind so the change is pulled again
+        let mut moved = feature("f1", "User authentication");
+        moved.status = "in_progress".to_string();
+        moved.graph_updated_at = 2;
+        assert!(!db.sync_feature_from_graph(&moved).unwrap());
+        assert!(db.get_feature("f1").unwrap().unwrap().passes);
+        assert_eq!(db.get_graph_updated_at("f1").unwrap(), Some(1));
+
+        db.pool
+            .write()
+            .execute("UPDATE feature_locks SET locked_until = '2000-01-01T00:00:00+00:00'", [])
+            .unwrap();
+
+        assert!(db.sync_feature_from_graph(&moved).unwrap());
+        let cached = db.get_feature("f1").unwrap().unwrap();
+        assert!(!cached.passes);
+        assert!(cached.in_progress);
+        assert_eq!(db.get_graph_updated_at("f1").unwrap(), Some(2));
+    }
+
+    #[test]
+    fn test_idempotency_keys_are_scoped_to_the_agent() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        let mut first = event("Bash", "/a", "{}");
+        first.idempotency_key = Some("retry-1".to_string());
+
+        let stored = db.insert_event(&first).unwrap();
+        assert!(!stored.is_duplicate());
+        assert_eq!(db.insert_event(&first).unwrap(), InsertedEvent::Duplicate(stored.id()));
+
+        // Another agent picking the same key is a different event
+        let mut other = first.clone();
+        other.source_agent = "antigravity".to_string();
+        assert!(!db.insert_event(&other).unwrap().is_duplicate());
+    }
+
+    #[test]
+    fn test_file_sync_records_history() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        let file_feature = |passes: bool| Feature {
+            id: "/a:0".to_string(),
+            project_dir: "/a".to_string(),
+            description: "User authentication".to_string(),
+            category: "functional".to_string(),
+            passes,
+            in_progress: false,
+            agent: None,
+            steps: None,
+            work_count: 0,
+            completion_criteria: None,
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            confidence: None,
+            model: None,
+            is_streaming: false,
+            retry_count: 0,
+            token_cost: None,
+            has_error: false,
+            last_agent_update: None,
+            manual_priority: None,
+            human_override_until: None,
+        };
+
+        db.sync_features("/a", vec![file_feature(false)]).unwrap();
+        db.sync_features("/a", vec![file_feature(false)]).unwrap();
+        assert!(db.get_feature_history("/a:0", 10).unwrap().is_empty());
+
+        db.sync_features("/a", vec![file_feature(true)]).unwrap();
+        let history = db.get_feature_history("/a:0", 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].field, "status");
+        assert_eq!(history[0].old_value.as_deref(), Some("pending"));
+        assert_eq!(history[0].new_value.as_deref(), Some("complete"));
+        assert_eq!(history[0].source, FILE_SYNC_SOURCE);
+    }
+}
@@ -0,0 +1,29 @@
+//! UI Events
+//!
+//! The core reports state changes (features updated, server status, ...) and
+//! user-facing notifications through an `EventSink`. The desktop app turns
+//! them into Tauri events and desktop notifications; headless, they are logged.
+
+use serde_json::Value;
+
+/// Receiver for UI-facing events from the core
+pub trait EventSink: Send + Sync + 'static {
+    /// Emit a named event with a JSON payload, e.g. `features-updated`
+    fn emit(&self, event: &str, payload: Value);
+
+    /// Show a desktop notification
+    fn notify(&self, title: &str, body: &str);
+}
+
+/// Sink for running without a UI
+pub struct LogSink;
+
+impl EventSink for LogSink {
+    fn emit(&self, event: &str, payload: Value) {
+        tracing::debug!("{}: {}", event, payload);
+    }
+
+    fn notify(&self, title: &str, body: &str) {
+        tracing::info!("{} {}", title, body);
+    }
+}
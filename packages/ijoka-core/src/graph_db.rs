@@ -0,0 +1,2343 @@
+//! Graph Database Module for Ijoka
+//!
+//! Provides connectivity to Memgraph/Neo4j for the source of truth data store.
+//! SQLite remains as a local read cache for fast UI rendering.
+
+use crate::db::{Database, UpdateSource};
+use anyhow::{Context, Result};
+use neo4rs::{query, ConfigBuilder, Graph, Node, Query, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+
+/// Number of outbox entries read from SQLite per replay batch
+const OUTBOX_BATCH_SIZE: i64 = 100;
+
+/// Replay attempts before an outbox entry is marked failed and skipped
+const MAX_OUTBOX_ATTEMPTS: i32 = 5;
+
+/// How often the supervisor probes a live connection
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Reconnect backoff bounds (doubles after each failed attempt)
+const RECONNECT_BACKOFF_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// A numbered set of schema statements (indexes and constraints)
+struct GraphSchemaStep {
+    version: i64,
+    description: &'static str,
+    statements: &'static [&'static str],
+}
+
+/// Memgraph schema applied on connect. Append new steps; never edit released ones.
+const MEMGRAPH_SCHEMA: &[GraphSchemaStep] = &[GraphSchemaStep {
+    version: 1,
+    description: "Indexes and unique constraints on node ids",
+    statements: &[
+        "CREATE INDEX ON :Project(path)",
+        "CREATE INDEX ON :Feature(id)",
+        "CREATE INDEX ON :Session(id)",
+        "CREATE INDEX ON :Event(id)",
+        "CREATE INDEX ON :Insight(id)",
+        "CREATE INDEX ON :Rule(id)",
+        "CREATE CONSTRAINT ON (p:Project) ASSERT p.path IS UNIQUE",
+        "CREATE CONSTRAINT ON (f:Feature) ASSERT f.id IS UNIQUE",
+        "CREATE CONSTRAINT ON (s:Session) ASSERT s.id IS UNIQUE",
+        "CREATE CONSTRAINT ON (e:Event) ASSERT e.id IS UNIQUE",
+        "CREATE CONSTRAINT ON (i:Insight) ASSERT i.id IS UNIQUE",
+        "CREATE CONSTRAINT ON (r:Rule) ASSERT r.id IS UNIQUE",
+    ],
+}];
+
+/// Neo4j 5 schema applied on connect. Unique constraints are backed by an
+/// index, so no separate indexes are created.
+const NEO4J_SCHEMA: &[GraphSchemaStep] = &[GraphSchemaStep {
+    version: 1,
+    description: "Unique constraints on node ids",
+    statements: &[
+        "CREATE CONSTRAINT project_path IF NOT EXISTS FOR (p:Project) REQUIRE p.path IS UNIQUE",
+        "CREATE CONSTRAINT feature_id IF NOT EXISTS FOR (f:Feature) REQUIRE f.id IS UNIQUE",
+        "CREATE CONSTRAINT session_id IF NOT EXISTS FOR (s:Session) REQUIRE s.id IS UNIQUE",
+        "CREATE CONSTRAINT event_id IF NOT EXISTS FOR (e:Event) REQUIRE e.id IS UNIQUE",
+        "CREATE CONSTRAINT insight_id IF NOT EXISTS FOR (i:Insight) REQUIRE i.id IS UNIQUE",
+        "CREATE CONSTRAINT rule_id IF NOT EXISTS FOR (r:Rule) REQUIRE r.id IS UNIQUE",
+    ],
+}];
+
+/// Graph database connection pool
+pub struct GraphDb {
+    graph: Arc<RwLock<Option<Graph>>>,
+    config: GraphDbConfig,
+    /// SQLite outbox that queues writes while disconnected
+    outbox: Option<Arc<Database>>,
+    /// Connection status, published on every connect/disconnect transition
+    status: watch::Sender<bool>,
+    /// Stands in for a graph that refuses status writes
+    #[cfg(test)]
+    refuse_status_writes: std::sync::Mutex<Option<StatusRefusal>>,
+}
+
+/// Builds the error a refused status write fails with, from the feature id
+#[cfg(test)]
+type StatusRefusal = fn(String) -> anyhow::Error;
+
+#[derive(Clone, Debug)]
+pub struct GraphDbConfig {
+    pub uri: String,
+    pub user: String,
+    pub password: String,
+    pub database: String,
+    pub dialect: GraphDialect,
+}
+
+impl Default for GraphDbConfig {
+    fn default() -> Self {
+        let dialect = std::env::var("IJOKA_GRAPH_DIALECT")
+            .ok()
+            .and_then(|d| {
+                let parsed = GraphDialect::parse(&d);
+                if parsed.is_none() {
+                    tracing::warn!("Unknown IJOKA_GRAPH_DIALECT {:?}, using memgraph", d);
+                }
+                parsed
+            })
+            .unwrap_or_default();
+
+        Self {
+            uri: std::env::var("IJOKA_GRAPH_URI")
+                .unwrap_or_else(|_| "bolt://localhost:7687".to_string()),
+            user: std::env::var("IJOKA_GRAPH_USER").unwrap_or_else(|_| "".to_string()),
+            password: std::env::var("IJOKA_GRAPH_PASSWORD")
+                .unwrap_or_else(|_| "".to_string()),
+            database: std::env::var("IJOKA_GRAPH_DATABASE")
+                .unwrap_or_else(|_| dialect.default_database().to_string()),
+            dialect,
+        }
+    }
+}
+
+/// Graph database flavour, selecting the database name, schema syntax and
+/// datetime handling
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphDialect {
+    #[default]
+    Memgraph,
+    /// Neo4j 5
+    Neo4j,
+}
+
+impl GraphDialect {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "memgraph" => Some(GraphDialect::Memgraph),
+            "neo4j" => Some(GraphDialect::Neo4j),
+            _ => None,
+        }
+    }
+
+    /// Database name used when IJOKA_GRAPH_DATABASE is not set
+    pub fn default_database(&self) -> &'static str {
+        match self {
+            // Memgraph only accepts "memgraph" as db name
+            GraphDialect::Memgraph => "memgraph",
+            GraphDialect::Neo4j => "neo4j",
+        }
+    }
+
+    /// Cypher expression converting a datetime expression to microseconds
+    /// since epoch (null stays null)
+    fn epoch_micros(&self, datetime: &str) -> String {
+        match self {
+            GraphDialect::Memgraph => format!("timestamp({})", datetime),
+            GraphDialect::Neo4j => format!(
+                "({dt}.epochSeconds * 1000000 + {dt}.microsecond)",
+                dt = datetime
+            ),
+        }
+    }
+
+    fn schema(&self) -> &'static [GraphSchemaStep] {
+        match self {
+            GraphDialect::Memgraph => MEMGRAPH_SCHEMA,
+            GraphDialect::Neo4j => NEO4J_SCHEMA,
+        }
+    }
+}
+
+impl GraphDb {
+    /// Create a new GraphDb instance with default config
+    pub fn new() -> Self {
+        Self::with_config(GraphDbConfig::default())
+    }
+
+    /// Create a new GraphDb instance with custom config
+    pub fn with_config(config: GraphDbConfig) -> Self {
+        Self {
+            graph: Arc::new(RwLock::new(None)),
+            config,
+            outbox: None,
+            status: watch::channel(false).0,
+            #[cfg(test)]
+            refuse_status_writes: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Connection settings in use
+    pub fn config(&self) -> &GraphDbConfig {
+        &self.config
+    }
+
+    /// Queue writes in the SQLite outbox while disconnected instead of failing them
+    pub fn with_outbox(mut self, database: Arc<Database>) -> Self {
+        self.outbox = Some(database);
+        self
+    }
+
+    /// Fail every status write with the given error, as a live graph would
+    #[cfg(test)]
+    pub(crate) fn refuse_status_writes(&self, refusal: StatusRefusal) {
+        *self.refuse_status_writes.lock().unwrap() = Some(refusal);
+    }
+
+    /// Connect to the graph database
+    pub async fn connect(&self) -> Result<()> {
+        tracing::debug!(
+            "Connecting to {:?} graph database {} at {}",
+            self.config.dialect,
+            self.config.database,
+            self.config.uri
+        );
+
+        // Set the database explicitly: neo4rs defaults to "neo4j", which
+        // Memgraph doesn't support
+        let config = ConfigBuilder::new()
+            .uri(&self.config.uri)
+            .user(&self.config.user)
+            .password(&self.config.password)
+            .db(self.config.database.as_str())
+            .build()
+            .context("Failed to build graph config")?;
+
+        let graph = Graph::connect(config)
+            .await
+            .context("Failed to connect to graph database")?;
+
+        // Verify connection with a simple query
+        let mut result = graph.execute(query("RETURN 1 as n")).await?;
+        if result.next().await?.is_some() {
+            tracing::info!("Successfully connected to graph database");
+        }
+
+        // A schema failure (e.g. duplicate ids blocking a constraint) shouldn't
+        // keep us offline; the step is retried on the next connect
+        if let Err(e) = Self::apply_schema(&graph, self.config.dialect).await {
+            tracing::warn!("Failed to apply graph schema: {:#}", e);
+        }
+
+        // Hold the write lock while draining the outbox so that writes issued
+        // during replay wait and are applied after the queued ones
+        let mut guard = self.graph.write().await;
+        let replayed = self.replay_outbox(&graph).await?;
+        if replayed > 0 {
+            tracing::info!("Replayed {} queued graph writes from outbox", replayed);
+        }
+        *guard = Some(graph);
+        self.status.send_replace(true);
+
+        Ok(())
+    }
+
+    /// Apply graph schema steps newer than the version recorded in the graph
+    async fn apply_schema(graph: &Graph, dialect: GraphDialect) -> Result<i64> {
+        let mut result = graph
+            .execute(query("MATCH (s:SchemaVersion {name: 'ijoka'}) RETURN s.version AS version"))
+            .await?;
+        let applied: i64 = match result.next().await? {
+            Some(row) => row.get("version").unwrap_or(0),
+            None => 0,
+        };
+
+        let mut current = applied;
+        for step in dialect.schema().iter().filter(|s| s.version > applied) {
+            for statement in step.statements {
+                graph.run(query(statement)).await.with_context(|| {
+                    format!("Graph schema {} ({}) failed on `{}`", step.version, step.description, statement)
+                })?;
+            }
+
+            graph
+                .run(
+                    query(
+                        r#"
+                        MERGE (s:SchemaVersion {name: 'ijoka'})
+                        SET s.version = $version, s.applied_at = datetime()
+                        "#,
+                    )
+                    .param("version", step.version),
+                )
+                .await?;
+
+            current = step.version;
+            tracing::info!("Applied graph schema {}: {}", step.version, step.description);
+        }
+
+        Ok(current)
+    }
+
+    /// Check if connected to the graph database
+    pub async fn is_connected(&self) -> bool {
+        self.graph.read().await.is_some()
+    }
+
+    /// Subscribe to connection status transitions (true = connected)
+    pub fn subscribe_status(&self) -> watch::Receiver<bool> {
+        self.status.subscribe()
+    }
+
+    /// Drop the cached connection so callers fall back to SQLite and the
+    /// supervisor starts reconnecting
+    pub async fn disconnect(&self) {
+        let mut guard = self.graph.write().await;
+        if guard.take().is_some() {
+            tracing::warn!("Disconnected from graph database");
+        }
+        self.status.send_if_modified(|connected| std::mem::replace(connected, false));
+    }
+
+    /// Probe the connection with a trivial query
+    pub async fn ping(&self) -> Result<()> {
+        self.fetch(query("RETURN 1 as n")).await?;
+        Ok(())
+    }
+
+    /// Keep the connection alive for the lifetime of the app: probe it while
+    /// connected and reconnect with exponential backoff while disconnected.
+    pub async fn supervise(&self) {
+        let mut backoff = RECONNECT_BACKOFF_MIN;
+        let mut attempts = 0u32;
+
+        loop {
+            if self.is_connected().await {
+                tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+                if let Err(e) = self.ping().await {
+                    tracing::warn!("Graph database health check failed: {}", e);
+                    self.disconnect().await;
+                }
+                continue;
+            }
+
+            match self.connect().await {
+                Ok(()) => {
+                    backoff = RECONNECT_BACKOFF_MIN;
+                    attempts = 0;
+                }
+                Err(e) => {
+                    attempts += 1;
+                    // Only the first failure is worth a warning; retries are expected
+                    if attempts == 1 {
+                        tracing::warn!(
+                            "Graph database not available: {}. Using SQLite-only mode.",
+                            e
+                        );
+                    } else {
+                        tracing::debug!("Graph reconnect attempt {} failed: {}", attempts, e);
+                    }
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(RECONNECT_BACKOFF_MAX);
+                }
+            }
+        }
+    }
+
+    /// Get the graph connection (panics if not connected)
+    async fn get_graph(&self) -> Result<Graph> {
+        let guard = self.graph.read().await;
+        guard
+            .clone()
+            .ok_or_else(|| anyhow::Error::new(NotConnected))
+    }
+
+    /// Execute a read query and collect its rows
+    async fn fetch(&self, q: Query) -> Result<Vec<Row>> {
+        let graph = self.get_graph().await?;
+
+        let result: Result<Vec<Row>> = async {
+            let mut stream = graph.execute(q).await?;
+            let mut rows = Vec::new();
+            while let Some(row) = stream.next().await? {
+                rows.push(row);
+            }
+            Ok(rows)
+        }
+        .await;
+
+        self.check_connection(result).await
+    }
+
+    /// Execute a query that returns no rows
+    async fn run(&self, q: Query) -> Result<()> {
+        let graph = self.get_graph().await?;
+        let result = graph.run(q).await.map_err(anyhow::Error::from);
+        self.check_connection(result).await
+    }
+
+    /// Drop the cached connection if a query failed because the link is down
+    async fn check_connection<T>(&self, result: Result<T>) -> Result<T> {
+        if let Err(e) = &result {
+            if is_connection_error(e) {
+                self.disconnect().await;
+            }
+        }
+        result
+    }
+
+    // =========================================================================
+    // WRITE PATH & OUTBOX
+    // =========================================================================
+
+    /// Apply a mutation, queueing it in the SQLite outbox when disconnected
+    async fn write(&self, mutation: GraphMutation) -> Result<()> {
+        #[cfg(test)]
+        if let Some(refuse) = *self.refuse_status_writes.lock().unwrap() {
+            if let GraphMutation::UpdateFeatureStatus { feature_id, .. }
+            | GraphMutation::CompleteFeature { feature_id, .. } = &mutation
+            {
+                return Err(refuse(feature_id.clone()));
+            }
+        }
+
+        let graph = self.graph.read().await.clone();
+
+        if let Some(graph) = graph {
+            match Self::apply(&graph, self.config.dialect, &mutation).await {
+                // The link dropped mid-write: queue it, replay is idempotent
+                Err(e) if is_connection_error(&e) => self.disconnect().await,
+                result => return result,
+            }
+        }
+
+        let Some(outbox) = &self.outbox else {
+            return Err(anyhow::Error::new(NotConnected));
+        };
+
+        let payload = serde_json::to_string(&mutation)?;
+        outbox.enqueue_outbox(mutation.operation(), &payload)?;
+        tracing::debug!("Graph offline, queued {} in outbox", mutation.operation());
+        Ok(())
+    }
+
+    /// Replay queued outbox mutations in order against a freshly connected graph.
+    /// Returns the number of mutations applied.
+    async fn replay_outbox(&self, graph: &Graph) -> Result<usize> {
+        let Some(outbox) = &self.outbox else {
+            return Ok(0);
+        };
+
+        let mut replayed = 0;
+        // Features whose queued status lost to a newer graph edit
+        let mut conflicted = HashSet::new();
+        loop {
+            let entries = outbox.get_pending_outbox(OUTBOX_BATCH_SIZE)?;
+            if entries.is_empty() {
+                return Ok(replayed);
+            }
+
+            for entry in entries {
+                let result = match serde_json::from_str::<GraphMutation>(&entry.payload) {
+                    Ok(mutation) => match without_conflicted_status(mutation, &conflicted) {
+                        Some(mutation) => Self::apply(graph, self.config.dialect, &mutation).await,
+                        None => Ok(()),
+                    },
+                    Err(e) => Err(e.into()),
+                };
+
+                match result {
+                    Ok(()) => {
+                        outbox.remove_outbox_entry(entry.id)?;
+                        replayed += 1;
+                    }
+                    // A human locked the status while this write was queued
+                    Err(e) if e.is::<StatusLocked>() => {
+                        tracing::info!("Dropping outbox entry {} #{}: {}", entry.operation, entry.id, e);
+                        outbox.remove_outbox_entry(entry.id)?;
+                    }
+                    // The graph changed after the offline edit was made: the graph
+                    // wins, so give up the edit's status lock and let sync pull it
+                    Err(e) if e.is::<FeatureChanged>() => {
+                        tracing::warn!("Dropping outbox entry {} #{}: {}", entry.operation, entry.id, e);
+                        let feature_id = e.downcast_ref::<FeatureChanged>().unwrap().0.clone();
+                        let status = ["status".to_string()];
+                        outbox.release_override(&feature_id, Some(&status))?;
+                        let release = GraphMutation::ReleaseFieldLocks {
+                            feature_id: feature_id.clone(),
+                            fields: Some(status.to_vec()),
+                        };
+                        Self::apply(graph, self.config.dialect, &release).await?;
+                        outbox.remove_outbox_entry(entry.id)?;
+                        conflicted.insert(feature_id);
+                    }
+                    Err(e) => {
+                        let gave_up =
+                            outbox.record_outbox_failure(entry.id, &e.to_string(), MAX_OUTBOX_ATTEMPTS)?;
+                        if !gave_up {
+                            // Stop here so later mutations are not applied out of order
+                            return Err(e.context(format!(
+                                "Outbox replay stopped at {} #{}",
+                                entry.operation, entry.id
+                            )));
+                        }
+                        tracing::warn!(
+                            "Dropping outbox entry {} #{} after {} attempts: {}",
+                            entry.operation,
+                            entry.id,
+                            entry.attempts + 1,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Execute a mutation against the graph. Every statement is keyed on a
+    /// client-generated id (MERGE rather than CREATE) so replays are idempotent.
+    async fn apply(graph: &Graph, dialect: GraphDialect, mutation: &GraphMutation) -> Result<()> {
+        let q = match mutation {
+            GraphMutation::UpsertProject { project } => query(
+                r#"
+                MERGE (p:Project {path: $path})
+                ON CREATE SET
+                    p.id = $id,
+                    p.name = $name,
+                    p.description = $description,
+                    p.created_at = datetime(),
+                    p.updated_at = datetime(),
+                    p.settings = $settings
+                ON MATCH SET
+                    p.name = $name,
+                    p.description = $description,
+                    p.updated_at = datetime(),
+                    p.settings = $settings
+                RETURN p
+                "#,
+            )
+            .param("id", project.id.clone())
+            .param("path", project.path.clone())
+            .param("name", project.name.clone())
+            .param("description", project.description.clone().unwrap_or_default())
+            .param(
+                "settings",
+                serde_json::to_string(&project.settings).unwrap_or_default(),
+            ),
+
+            GraphMutation::EnsureProject { project } => query(
+                r#"
+                MERGE (p:Project {path: $path})
+                ON CREATE SET
+                    p.id = $id,
+                    p.name = $name,
+                    p.description = $description,
+                    p.created_at = datetime(),
+                    p.updated_at = datetime(),
+                    p.settings = $settings
+                "#,
+            )
+            .param("id", project.id.clone())
+            .param("path", project.path.clone())
+            .param("name", project.name.clone())
+            .param("description", project.description.clone().unwrap_or_default())
+            .param(
+                "settings",
+                serde_json::to_string(&project.settings).unwrap_or_default(),
+            ),
+
+            GraphMutation::CreateFeature {
+                id,
+                project_path,
+                description,
+                category,
+                status,
+                priority,
+                steps,
+            } => query(
+                r#"
+                MATCH (p:Project {path: $project_path})
+                MERGE (f:Feature {id: $id})
+                ON CREATE SET
+                    f.description = $description,
+                    f.category = $category,
+                    f.status = $status,
+                    f.priority = $priority,
+                    f.steps = $steps,
+                    f.created_at = datetime(),
+                    f.updated_at = datetime(),
+                    f.work_count = 0
+                MERGE (f)-[:BELONGS_TO]->(p)
+                RETURN f.id as id
+                "#,
+            )
+            .param("project_path", project_path.clone())
+            .param("id", id.clone())
+            .param("description", description.clone())
+            .param("category", category.clone())
+            .param("status", status.clone())
+            .param("priority", *priority)
+            .param("steps", steps.clone()),
+
+            // Status writers skip features whose status a human has locked,
+            // unless the write is the human's own
+            GraphMutation::UpdateFeatureStatus {
+                feature_id,
+                status,
+                human,
+                expected_changed_at,
+            } => query(&format!(
+                r#"
+                {}
+                FOREACH (_ IN CASE WHEN unlocked AND current THEN [1] ELSE [] END |
+                    SET f.status = $status, f.updated_at = datetime())
+                RETURN unlocked, current
+                "#,
+                status_write_guard(dialect)
+            ))
+            .param("id", feature_id.clone())
+            .param("status", status.clone())
+            .param("human", *human)
+            .param("expected", *expected_changed_at)
+            .param("now", chrono::Utc::now().to_rfc3339()),
+
+            GraphMutation::ActivateFeature { feature_id, human } => query(&format!(
+                r#"
+                {}
+                FOREACH (_ IN CASE WHEN unlocked AND current THEN [1] ELSE [] END |
+                    SET f.status = 'in_progress', f.updated_at = datetime())
+                RETURN unlocked, current
+                "#,
+                status_write_guard(dialect)
+            ))
+            .param("id", feature_id.clone())
+            .param("human", *human)
+            .param("expected", None::<i64>)
+            .param("now", chrono::Utc::now().to_rfc3339()),
+
+            GraphMutation::CompleteFeature {
+                feature_id,
+                human,
+                expected_changed_at,
+            } => query(&format!(
+                r#"
+                {}
+                FOREACH (_ IN CASE WHEN unlocked AND current THEN [1] ELSE [] END |
+                    SET f.status = 'complete', f.completed_at = datetime(), f.updated_at = datetime())
+                RETURN unlocked, current
+                "#,
+                status_write_guard(dialect)
+            ))
+            .param("id", feature_id.clone())
+            .param("human", *human)
+            .param("expected", *expected_changed_at)
+            .param("now", chrono::Utc::now().to_rfc3339()),
+
+            GraphMutation::DeleteFeature { feature_id } => query(
+                r#"
+                MATCH (f:Feature {id: $id})
+                OPTIONAL MATCH (f)-[:HAS_LOCK]->(l:FieldLock)
+                DETACH DELETE f, l
+                "#,
+            )
+            .param("id", feature_id.clone()),
+
+            GraphMutation::LockFields { feature_id, fields, until } => query(
+                r#"
+                MATCH (f:Feature {id: $id})
+                UNWIND $fields AS field
+                MERGE (f)-[:HAS_LOCK]->(l:FieldLock {feature_id: $id, field: field})
+                SET l.until = $until
+                "#,
+            )
+            .param("id", feature_id.clone())
+            .param("fields", fields.clone())
+            .param("until", until.clone()),
+
+            GraphMutation::ReleaseFieldLocks { feature_id, fields } => query(
+                r#"
+                MATCH (:Feature {id: $id})-[:HAS_LOCK]->(l:FieldLock)
+                WHERE $fields IS NULL OR l.field IN $fields
+                DETACH DELETE l
+                "#,
+            )
+            .param("id", feature_id.clone())
+            .param("fields", fields.clone()),
+
+            GraphMutation::RecordStatusChange { change } => query(
+                r#"
+                MATCH (f:Feature {id: $feature_id})
+                MERGE (c:StatusChange {id: $id})
+                ON CREATE SET
+                    c.from_status = $from_status,
+                    c.to_status = $to_status,
+                    c.source = $source,
+                    c.agent = $agent,
+                    c.session_id = $session_id,
+                    c.changed_at = datetime($changed_at)
+                MERGE (f)-[:STATUS_CHANGED]->(c)
+                WITH c
+                OPTIONAL MATCH (s:Session {id: $session_id})
+                FOREACH (_ IN CASE WHEN s IS NULL THEN [] ELSE [1] END |
+                    MERGE (c)-[:MADE_IN]->(s))
+                "#,
+            )
+            .param("feature_id", change.feature_id.clone())
+            .param("id", change.id.clone())
+            .param("from_status", change.from_status.clone())
+            .param("to_status", change.to_status.clone())
+            .param("source", change.source.clone())
+            .param("agent", change.agent.clone().unwrap_or_default())
+            .param("session_id", change.session_id.clone().unwrap_or_default())
+            .param("changed_at", change.changed_at.clone()),
+
+            GraphMutation::RecordEvent { event, session_id } => query(
+                r#"
+                MATCH (s:Session {id: $session_id})
+                MERGE (e:Event {id: $id})
+                ON CREATE SET
+                    e.event_type = $event_type,
+                    e.tool_name = $tool_name,
+                    e.payload = $payload,
+                    e.summary = $summary,
+                    e.source_agent = $source_agent,
+                    e.timestamp = CASE WHEN $timestamp = '' THEN datetime() ELSE datetime($timestamp) END,
+                    e.success = $success,
+                    e.tool_input = $tool_input,
+                    e.exit_code = $exit_code,
+                    e.duration_ms = $duration_ms,
+                    s.event_count = coalesce(s.event_count, 0) + 1
+                MERGE (e)-[:TRIGGERED_BY]->(s)
+                RETURN e.id as id
+                "#,
+            )
+            .param("session_id", session_id.clone())
+            .param("id", event.id.clone().unwrap_or_default())
+            .param("event_type", event.event_type.clone())
+            .param("tool_name", event.tool_name.clone().unwrap_or_default())
+            .param(
+                "payload",
+                serde_json::to_string(&event.payload).unwrap_or_default(),
+            )
+            .param("summary", event.summary.clone().unwrap_or_default())
+            .param("source_agent", event.source_agent.clone().unwrap_or_default())
+            .param("timestamp", event.timestamp.clone().unwrap_or_default())
+            .param("success", event.success.unwrap_or(true))
+            .param("tool_input", event.tool_input.as_ref().map(|input| input.to_string()))
+            .param("exit_code", event.exit_code)
+            .param("duration_ms", event.duration_ms),
+
+            GraphMutation::LinkEventToFeature {
+                event_id,
+                feature_id,
+            } => query(
+                r#"
+                MATCH (e:Event {id: $event_id}), (f:Feature {id: $feature_id})
+                MERGE (e)-[:LINKED_TO]->(f)
+                "#,
+            )
+            .param("event_id", event_id.clone())
+            .param("feature_id", feature_id.clone()),
+
+            GraphMutation::StartSession {
+                session_id,
+                agent,
+                project_path,
+            } => query(
+                r#"
+                MATCH (p:Project {path: $project_path})
+                MERGE (s:Session {id: $id})
+                ON CREATE SET
+                    s.agent = $agent,
+                    s.status = 'active',
+                    s.started_at = datetime(),
+                    s.last_activity = datetime(),
+                    s.event_count = 0,
+                    s.is_subagent = false
+                MERGE (s)-[:IN_PROJECT]->(p)
+                "#,
+            )
+            .param("project_path", project_path.clone())
+            .param("id", session_id.clone())
+            .param("agent", agent.clone()),
+
+            GraphMutation::EndSession { session_id } => query(
+                r#"
+                MATCH (s:Session {id: $id})
+                SET s.status = 'ended', s.ended_at = datetime()
+                "#,
+            )
+            .param("id", session_id.clone()),
+
+            // Count the linked events rather than incrementing, so a replay
+            // doesn't count the same event twice
+            GraphMutation::UpdateSessionActivity { session_id } => query(
+                r#"
+                MATCH (s:Session {id: $id})
+                SET s.last_activity = datetime()
+                "#,
+            )
+            .param("id", session_id.clone()),
+
+            GraphMutation::RecordInsight { insight, event_id } => query(
+                r#"
+                MERGE (i:Insight {id: $id})
+                ON CREATE SET
+                    i.description = $description,
+                    i.pattern_type = $pattern_type,
+                    i.tags = $tags,
+                    i.created_at = datetime(),
+                    i.usage_count = 0,
+                    i.effectiveness_score = $effectiveness_score
+                WITH i
+                OPTIONAL MATCH (e:Event {id: $event_id})
+                FOREACH (_ IN CASE WHEN e IS NULL THEN [] ELSE [1] END |
+                    MERGE (i)-[:LEARNED_FROM]->(e))
+                RETURN i.id as id
+                "#,
+            )
+            .param("id", insight.id.clone().unwrap_or_default())
+            .param("description", insight.description.clone())
+            .param("pattern_type", insight.pattern_type.clone())
+            .param("tags", insight.tags.clone().unwrap_or_default())
+            .param("effectiveness_score", insight.effectiveness_score.unwrap_or(0.0))
+            .param("event_id", event_id.clone().unwrap_or_default()),
+
+            GraphMutation::CreateRule { rule, project_path } => query(
+                r#"
+                MERGE (r:Rule {id: $id})
+                ON CREATE SET
+                    r.name = $name,
+                    r.description = $description,
+                    r.trigger = $trigger,
+                    r.action = $action,
+                    r.scope = $scope,
+                    r.enforcement = $enforcement,
+                    r.enabled = $enabled,
+                    r.created_at = datetime(),
+                    r.triggered_count = 0,
+                    r.source_instruction_count = $source_instruction_count
+                WITH r
+                OPTIONAL MATCH (p:Project {path: $project_path})
+                FOREACH (_ IN CASE WHEN p IS NULL OR r.scope <> 'project' THEN [] ELSE [1] END |
+                    MERGE (r)-[:APPLIES_TO]->(p))
+                RETURN r.id as id
+                "#,
+            )
+            .param("id", rule.id.clone().unwrap_or_default())
+            .param("name", rule.name.clone())
+            .param("description", rule.description.clone())
+            .param("trigger", serde_json::to_string(&rule.trigger).unwrap_or_default())
+            .param("action", serde_json::to_string(&rule.action).unwrap_or_default())
+            .param("scope", rule.scope.clone())
+            .param("enforcement", rule.enforcement.clone())
+            .param("enabled", rule.enabled.unwrap_or(true))
+            .param(
+                "source_instruction_count",
+                rule.source_instruction_count.unwrap_or(0) as i64,
+            )
+            .param("project_path", project_path.clone().unwrap_or_default()),
+
+            GraphMutation::ToggleRule { rule_id, enabled } => query(
+                r#"
+                MATCH (r:Rule {id: $id})
+                SET r.enabled = $enabled
+                "#,
+            )
+            .param("id", rule_id.clone())
+            .param("enabled", *enabled),
+        };
+
+        match mutation {
+            GraphMutation::UpdateFeatureStatus { feature_id, .. }
+            | GraphMutation::ActivateFeature { feature_id, .. }
+            | GraphMutation::CompleteFeature { feature_id, .. } => {
+                // No row means the feature is gone; otherwise the guard says
+                // whether a newer graph edit or a human lock held the write
+                let mut result = graph.execute(q).await?;
+                if let Some(row) = result.next().await? {
+                    if !row.get::<bool>("current")? {
+                        return Err(anyhow::Error::new(FeatureChanged(feature_id.clone())));
+                    }
+                    if !row.get::<bool>("unlocked")? {
+                        return Err(anyhow::Error::new(StatusLocked(feature_id.clone())));
+                    }
+                }
+            }
+            _ => graph.run(q).await?,
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // PROJECT OPERATIONS
+    // =========================================================================
+
+    /// Create or update a project
+    pub async fn upsert_project(&self, project: &Project) -> Result<()> {
+        self.write(GraphMutation::UpsertProject {
+            project: project.clone(),
+        })
+        .await
+    }
+
+    /// Create a project unless one already exists at its path. Never
+    /// modifies an existing project, even when replayed from the outbox.
+    pub async fn ensure_project(&self, project: &Project) -> Result<()> {
+        self.write(GraphMutation::EnsureProject {
+            project: project.clone(),
+        })
+        .await
+    }
+
+    /// Get all projects
+    pub async fn get_projects(&self) -> Result<Vec<Project>> {
+        let q = query("MATCH (p:Project) RETURN p ORDER BY p.name");
+        let rows = self.fetch(q).await?;
+
+        let mut projects = Vec::new();
+        for row in rows {
+            let node: Node = row.get("p")?;
+            projects.push(Project::from_node(&node)?);
+        }
+
+        Ok(projects)
+    }
+
+    /// Get project by path
+    pub async fn get_project_by_path(&self, path: &str) -> Result<Option<Project>> {
+        let q = query("MATCH (p:Project {path: $path}) RETURN p").param("path", path);
+        let rows = self.fetch(q).await?;
+
+        if let Some(row) = rows.into_iter().next() {
+            let node: Node = row.get("p")?;
+            Ok(Some(Project::from_node(&node)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // =========================================================================
+    // FEATURE OPERATIONS
+    // =========================================================================
+
+    /// Create a new feature linked to a project
+    pub async fn create_feature(&self, feature: &Feature, project_path: &str) -> Result<String> {
+        let feature_id = feature
+            .id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        self.write(GraphMutation::CreateFeature {
+            id: feature_id.clone(),
+            project_path: project_path.to_string(),
+            description: feature.description.clone(),
+            category: feature.category.clone(),
+            status: feature.status.clone(),
+            priority: feature.priority.unwrap_or(0) as i64,
+            steps: feature.steps.clone().unwrap_or_default(),
+        })
+        .await?;
+        Ok(feature_id)
+    }
+
+    /// Get all features for a project
+    pub async fn get_features_for_project(&self, project_path: &str) -> Result<Vec<Feature>> {
+        let q = query(
+            r#"
+            MATCH (f:Feature)-[:BELONGS_TO]->(p:Project {path: $project_path})
+            RETURN f
+            ORDER BY f.priority DESC, f.created_at DESC
+            "#,
+        )
+        .param("project_path", project_path);
+
+        let rows = self.fetch(q).await?;
+
+        let mut features = Vec::new();
+        for row in rows {
+            let node: Node = row.get("f")?;
+            let mut feature = Feature::from_node(&node)?;
+            feature.project_dir = Some(project_path.to_string());
+            features.push(feature);
+        }
+
+        Ok(features)
+    }
+
+    /// Get features across the given projects whose `updated_at` is newer than
+    /// `since` (microseconds since epoch), in a single batched query.
+    /// Features are returned oldest change first, with `project_dir` set.
+    pub async fn get_features_changed_since(
+        &self,
+        project_paths: &[String],
+        since: i64,
+    ) -> Result<FeatureChanges> {
+        let q = query(&format!(
+            r#"
+            UNWIND $project_paths AS project_path
+            MATCH (f:Feature)-[:BELONGS_TO]->(:Project {{path: project_path}})
+            WITH f, project_path, coalesce({}, 0) AS changed_at
+            WHERE changed_at > $since
+            RETURN f, project_path, changed_at
+            ORDER BY changed_at
+            "#,
+            self.config.dialect.epoch_micros("coalesce(f.updated_at, f.created_at)")
+        ))
+        .param("project_paths", project_paths.to_vec())
+        .param("since", since);
+
+        let rows = self.fetch(q).await?;
+
+        let mut changes = FeatureChanges {
+            features: Vec::new(),
+            watermark: since,
+        };
+        for row in rows {
+            let node: Node = row.get("f")?;
+            let mut feature = Feature::from_node(&node)?;
+            feature.project_dir = Some(row.get("project_path")?);
+            let changed_at: i64 = row.get("changed_at")?;
+            changes.watermark = changes.watermark.max(changed_at);
+            changes.features.push((feature, changed_at));
+        }
+
+        Ok(changes)
+    }
+
+    /// Get a feature's `updated_at` (microseconds since epoch), or None if the
+    /// feature does not exist in the graph
+    pub async fn get_feature_changed_at(&self, feature_id: &str) -> Result<Option<i64>> {
+        let q = query(&format!(
+            r#"
+            MATCH (f:Feature {{id: $id}})
+            RETURN coalesce({}, 0) AS changed_at
+            "#,
+            self.config.dialect.epoch_micros("coalesce(f.updated_at, f.created_at)")
+        ))
+        .param("id", feature_id);
+
+        let rows = self.fetch(q).await?;
+        match rows.into_iter().next() {
+            Some(row) => Ok(Some(row.get("changed_at")?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the project path every feature belongs to (feature id -> project path).
+    /// Used to reconcile deletions and moves into the SQLite cache.
+    pub async fn get_feature_memberships(&self) -> Result<HashMap<String, String>> {
+        let q = query(
+            r#"
+            MATCH (f:Feature)-[:BELONGS_TO]->(p:Project)
+            WHERE f.id IS NOT NULL
+            RETURN f.id as id, p.path as project_path
+            "#,
+        );
+
+        let rows = self.fetch(q).await?;
+
+        let mut memberships = HashMap::with_capacity(rows.len());
+        for row in rows {
+            memberships.insert(row.get("id")?, row.get("project_path")?);
+        }
+
+        Ok(memberships)
+    }
+
+    /// Get active feature for a project (status = 'in_progress')
+    pub async fn get_active_feature(&self, project_path: &str) -> Result<Option<Feature>> {
+        let q = query(
+            r#"
+            MATCH (f:Feature {status: 'in_progress'})-[:BELONGS_TO]->(p:Project {path: $project_path})
+            RETURN f
+            LIMIT 1
+            "#,
+        )
+        .param("project_path", project_path);
+
+        let rows = self.fetch(q).await?;
+
+        if let Some(row) = rows.into_iter().next() {
+            let node: Node = row.get("f")?;
+            Ok(Some(Feature::from_node(&node)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Update feature status. Agent writes fail with `StatusLocked` while a
+    /// human holds the feature's status. With `expected_changed_at` (see
+    /// `get_feature_changed_at`) the write fails with `FeatureChanged` if the
+    /// feature changed after that, including when replayed from the outbox.
+    pub async fn update_feature_status(
+        &self,
+        feature_id: &str,
+        status: &str,
+        source: UpdateSource,
+        expected_changed_at: Option<i64>,
+    ) -> Result<()> {
+        self.write(GraphMutation::UpdateFeatureStatus {
+            feature_id: feature_id.to_string(),
+            status: status.to_string(),
+            human: matches!(source, UpdateSource::Human),
+            expected_changed_at,
+        })
+        .await
+    }
+
+    /// Activate a feature (set to in_progress)
+    /// Multiple features can be in_progress simultaneously
+    pub async fn activate_feature(&self, _project_path: &str, feature_id: &str, source: UpdateSource) -> Result<()> {
+        // Activate the specified feature (no longer deactivates others)
+        self.write(GraphMutation::ActivateFeature {
+            feature_id: feature_id.to_string(),
+            human: matches!(source, UpdateSource::Human),
+        })
+        .await
+    }
+
+    /// Complete a feature. Refused like `update_feature_status`.
+    pub async fn complete_feature(
+        &self,
+        feature_id: &str,
+        source: UpdateSource,
+        expected_changed_at: Option<i64>,
+    ) -> Result<()> {
+        self.write(GraphMutation::CompleteFeature {
+            feature_id: feature_id.to_string(),
+            human: matches!(source, UpdateSource::Human),
+            expected_changed_at,
+        })
+        .await
+    }
+
+    /// Fields of a feature held by an unexpired mirrored human lock
+    pub async fn get_locked_fields(&self, feature_id: &str) -> Result<Vec<String>> {
+        let q = query(
+            r#"
+            MATCH (:Feature {id: $id})-[:HAS_LOCK]->(l:FieldLock)
+            WHERE l.until IS NULL OR l.until > $now
+            RETURN l.field AS field
+            ORDER BY field
+            "#,
+        )
+        .param("id", feature_id)
+        .param("now", chrono::Utc::now().to_rfc3339());
+
+        let rows = self.fetch(q).await?;
+        rows.into_iter()
+            .map(|row| row.get::<String>("field").map_err(Into::into))
+            .collect()
+    }
+
+    /// Delete a feature and its relationships (events keep their own nodes)
+    pub async fn delete_feature(&self, feature_id: &str) -> Result<()> {
+        self.write(GraphMutation::DeleteFeature {
+            feature_id: feature_id.to_string(),
+        })
+        .await
+    }
+
+    /// Mirror human field locks onto the feature as FieldLock nodes so
+    /// graph-side writers can respect them. `until` is RFC 3339 (UTC); None
+    /// holds the lock until released. Does not touch `updated_at`.
+    pub async fn lock_fields(&self, feature_id: &str, fields: &[String], until: Option<&str>) -> Result<()> {
+        self.write(GraphMutation::LockFields {
+            feature_id: feature_id.to_string(),
+            fields: fields.to_vec(),
+            until: until.map(str::to_string),
+        })
+        .await
+    }
+
+    /// Drop mirrored field locks: the given fields, or all of them
+    pub async fn release_field_locks(&self, feature_id: &str, fields: Option<&[String]>) -> Result<()> {
+        self.write(GraphMutation::ReleaseFieldLocks {
+            feature_id: feature_id.to_string(),
+            fields: fields.map(<[String]>::to_vec),
+        })
+        .await
+    }
+
+    /// Record a status move as a StatusChange node hung off the feature,
+    /// linked to the session it was made in when that session is known.
+    /// Replays are idempotent on `change.id`.
+    pub async fn record_status_change(&self, change: &StatusChange) -> Result<()> {
+        self.write(GraphMutation::RecordStatusChange { change: change.clone() })
+            .await
+    }
+
+    /// Increment work count for a feature
+    pub async fn increment_work_count(&self, feature_id: &str) -> Result<i64> {
+        let q = query(
+            r#"
+            MATCH (f:Feature {id: $id})
+            SET f.work_count = f.work_count + 1, f.updated_at = datetime()
+            RETURN f.work_count as count
+            "#,
+        )
+        .param("id", feature_id);
+
+        let rows = self.fetch(q).await?;
+        if let Some(row) = rows.into_iter().next() {
+            Ok(row.get::<i64>("count")?)
+        } else {
+            Ok(0)
+        }
+    }
+
+    // =========================================================================
+    // EVENT OPERATIONS
+    // =========================================================================
+
+    /// Record an event
+    pub async fn record_event(&self, event: &Event, session_id: &str) -> Result<String> {
+        let event_id = event
+            .id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        // Stamp the event now so a delayed outbox replay keeps the original time
+        let mut event = event.clone();
+        event.id = Some(event_id.clone());
+        event.timestamp.get_or_insert_with(|| {
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false)
+        });
+
+        self.write(GraphMutation::RecordEvent {
+            event,
+            session_id: session_id.to_string(),
+        })
+        .await?;
+        Ok(event_id)
+    }
+
+    /// Link an event to a feature
+    pub async fn link_event_to_feature(&self, event_id: &str, feature_id: &str) -> Result<()> {
+        self.write(GraphMutation::LinkEventToFeature {
+            event_id: event_id.to_string(),
+            feature_id: feature_id.to_string(),
+        })
+        .await
+    }
+
+    /// Get recent events for a project
+    pub async fn get_recent_events(&self, project_path: &str, limit: i64) -> Result<Vec<Event>> {
+        let q = query(
+            r#"
+            MATCH (e:Event)-[:TRIGGERED_BY]->(s:Session)-[:IN_PROJECT]->(p:Project {path: $project_path})
+            OPTIONAL MATCH (e)-[:LINKED_TO]->(f:Feature)
+            RETURN e.id as id,
+                   e.event_type as event_type,
+                   e.tool_name as tool_name,
+                   e.payload as payload,
+                   e.summary as summary,
+                   toString(e.timestamp) as timestamp,
+                   e.success as success,
+                   e.tool_input as tool_input,
+                   e.exit_code as exit_code,
+                   e.duration_ms as duration_ms,
+                   e.source_agent as source_agent,
+                   s.id as session_id,
+                   p.path as project_path,
+                   f.id as feature_id,
+                   f.description as feature_description
+            ORDER BY e.timestamp DESC
+            LIMIT $limit
+            "#,
+        )
+        .param("project_path", project_path)
+        .param("limit", limit);
+
+        let rows = self.fetch(q).await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let payload_str: Option<String> = row.get("payload").ok();
+            let payload: Option<serde_json::Value> = payload_str
+                .and_then(|s| serde_json::from_str(&s).ok());
+
+            events.push(Event {
+                id: row.get("id").ok(),
+                event_type: row.get("event_type")?,
+                tool_name: row.get("tool_name").ok(),
+                payload,
+                summary: row.get("summary").ok(),
+                timestamp: row.get("timestamp").ok(),
+                success: row.get("success").ok(),
+                tool_input: row
+                    .get::<String>("tool_input")
+                    .ok()
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+                exit_code: row.get("exit_code").ok(),
+                duration_ms: row.get("duration_ms").ok(),
+                source_agent: row.get("source_agent").ok(),
+                session_id: row.get("session_id").ok(),
+                project_path: row.get("project_path").ok(),
+                feature_id: row.get("feature_id").ok(),
+                feature_description: row.get("feature_description").ok(),
+            });
+        }
+
+        Ok(events)
+    }
+
+    /// Get recent events across all projects (global view)
+    pub async fn get_all_recent_events(&self, limit: i64) -> Result<Vec<Event>> {
+        let q = query(
+            r#"
+            MATCH (e:Event)
+            OPTIONAL MATCH (e)-[:TRIGGERED_BY]->(s:Session)-[:IN_PROJECT]->(p:Project)
+            OPTIONAL MATCH (e)-[:LINKED_TO]->(f:Feature)
+            RETURN e.id as id,
+                   e.event_type as event_type,
+                   e.tool_name as tool_name,
+                   e.payload as payload,
+                   e.summary as summary,
+                   toString(e.timestamp) as timestamp,
+                   e.success as success,
+                   e.tool_input as tool_input,
+                   e.exit_code as exit_code,
+                   e.duration_ms as duration_ms,
+                   e.source_agent as source_agent,
+                   s.id as session_id,
+                   p.path as project_path,
+                   f.id as feature_id,
+                   f.description as feature_description
+            ORDER BY e.timestamp DESC
+            LIMIT $limit
+            "#,
+        )
+        .param("limit", limit);
+
+        let rows = self.fetch(q).await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let payload_str: Option<String> = row.get("payload").ok();
+            let payload: Option<serde_json::Value> = payload_str
+                .and_then(|s| serde_json::from_str(&s).ok());
+
+            events.push(Event {
+                id: row.get("id").ok(),
+                event_type: row.get("event_type")?,
+                tool_name: row.get("tool_name").ok(),
+                payload,
+                summary: row.get("summary").ok(),
+                timestamp: row.get("timestamp").ok(),
+                success: row.get("success").ok(),
+                tool_input: row
+                    .get::<String>("tool_input")
+                    .ok()
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+                exit_code: row.get("exit_code").ok(),
+                duration_ms: row.get("duration_ms").ok(),
+                source_agent: row.get("source_agent").ok(),
+                session_id: row.get("session_id").ok(),
+                project_path: row.get("project_path").ok(),
+                feature_id: row.get("feature_id").ok(),
+                feature_description: row.get("feature_description").ok(),
+            });
+        }
+
+        Ok(events)
+    }
+
+    /// Get events linked to a specific feature
+    pub async fn get_events_by_feature(&self, feature_id: &str, limit: i64) -> Result<Vec<Event>> {
+        let q = query(
+            r#"
+            MATCH (e:Event)-[:LINKED_TO]->(f:Feature {id: $feature_id})
+            OPTIONAL MATCH (e)-[:TRIGGERED_BY]->(s:Session)-[:IN_PROJECT]->(p:Project)
+            RETURN e.id as id,
+                   e.event_type as event_type,
+                   e.tool_name as tool_name,
+                   e.payload as payload,
+                   e.summary as summary,
+                   toString(e.timestamp) as timestamp,
+                   e.success as success,
+                   e.tool_input as tool_input,
+                   e.exit_code as exit_code,
+                   e.duration_ms as duration_ms,
+                   e.source_agent as source_agent,
+                   s.id as session_id,
+                   p.path as project_path,
+                   f.description as feature_description
+            ORDER BY e.timestamp DESC
+            LIMIT $limit
+            "#,
+        )
+        .param("feature_id", feature_id)
+        .param("limit", limit);
+
+        let rows = self.fetch(q).await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let payload_str: Option<String> = row.get("payload").ok();
+            let payload: Option<serde_json::Value> = payload_str
+                .and_then(|s| serde_json::from_str(&s).ok());
+
+            events.push(Event {
+                id: row.get("id").ok(),
+                event_type: row.get("event_type")?,
+                tool_name: row.get("tool_name").ok(),
+                payload,
+                summary: row.get("summary").ok(),
+                timestamp: row.get("timestamp").ok(),
+                success: row.get("success").ok(),
+                tool_input: row
+                    .get::<String>("tool_input")
+                    .ok()
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+                exit_code: row.get("exit_code").ok(),
+                duration_ms: row.get("duration_ms").ok(),
+                source_agent: row.get("source_agent").ok(),
+                session_id: row.get("session_id").ok(),
+                project_path: row.get("project_path").ok(),
+                feature_id: Some(feature_id.to_string()),
+                feature_description: row.get("feature_description").ok(),
+            });
+        }
+
+        Ok(events)
+    }
+
+    // =========================================================================
+    // SESSION OPERATIONS
+    // =========================================================================
+
+    /// Start a new session
+    pub async fn start_session(
+        &self,
+        session_id: &str,
+        agent: &str,
+        project_path: &str,
+    ) -> Result<()> {
+        self.write(GraphMutation::StartSession {
+            session_id: session_id.to_string(),
+            agent: agent.to_string(),
+            project_path: project_path.to_string(),
+        })
+        .await
+    }
+
+    /// End a session
+    pub async fn end_session(&self, session_id: &str) -> Result<()> {
+        self.write(GraphMutation::EndSession {
+            session_id: session_id.to_string(),
+        })
+        .await
+    }
+
+    /// Update session activity
+    pub async fn update_session_activity(&self, session_id: &str) -> Result<()> {
+        self.write(GraphMutation::UpdateSessionActivity {
+            session_id: session_id.to_string(),
+        })
+        .await
+    }
+
+    /// Get active sessions for a project
+    pub async fn get_active_sessions(&self, project_path: &str) -> Result<Vec<Session>> {
+        let q = query(
+            r#"
+            MATCH (s:Session {status: 'active'})-[:IN_PROJECT]->(p:Project {path: $project_path})
+            RETURN s
+            ORDER BY s.last_activity DESC
+            "#,
+        )
+        .param("project_path", project_path);
+
+        let rows = self.fetch(q).await?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            let node: Node = row.get("s")?;
+            sessions.push(Session::from_node(&node)?);
+        }
+
+        Ok(sessions)
+    }
+
+    /// Get all sessions (global view)
+    pub async fn get_all_sessions(&self, limit: i64) -> Result<Vec<Session>> {
+        let q = query(
+            r#"
+            MATCH (s:Session)
+            RETURN s
+            ORDER BY s.last_activity DESC
+            LIMIT $limit
+            "#,
+        )
+        .param("limit", limit);
+
+        let rows = self.fetch(q).await?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            let node: Node = row.get("s")?;
+            sessions.push(Session::from_node(&node)?);
+        }
+
+        Ok(sessions)
+    }
+
+    // =========================================================================
+    // INSIGHT OPERATIONS
+    // =========================================================================
+
+    /// Record a new insight
+    pub async fn record_insight(&self, insight: &Insight, event_id: Option<&str>) -> Result<String> {
+        let insight_id = insight
+            .id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        let mut insight = insight.clone();
+        insight.id = Some(insight_id.clone());
+
+        // Links to the source event if provided
+        self.write(GraphMutation::RecordInsight {
+            insight,
+            event_id: event_id.map(String::from),
+        })
+        .await?;
+        Ok(insight_id)
+    }
+
+    /// Get insights by tags
+    pub async fn get_insights_by_tags(&self, tags: &[String], limit: i64) -> Result<Vec<Insight>> {
+        let q = query(
+            r#"
+            MATCH (i:Insight)
+            WHERE any(tag IN $tags WHERE tag IN i.tags)
+            RETURN i
+            ORDER BY i.usage_count DESC, i.created_at DESC
+            LIMIT $limit
+            "#,
+        )
+        .param("tags", tags.to_vec())
+        .param("limit", limit);
+
+        let rows = self.fetch(q).await?;
+
+        let mut insights = Vec::new();
+        for row in rows {
+            let node: Node = row.get("i")?;
+            insights.push(Insight::from_node(&node)?);
+        }
+
+        Ok(insights)
+    }
+
+    /// Get insights by pattern type
+    pub async fn get_insights_by_type(&self, pattern_type: &str, limit: i64) -> Result<Vec<Insight>> {
+        let q = query(
+            r#"
+            MATCH (i:Insight {pattern_type: $pattern_type})
+            RETURN i
+            ORDER BY i.usage_count DESC, i.created_at DESC
+            LIMIT $limit
+            "#,
+        )
+        .param("pattern_type", pattern_type)
+        .param("limit", limit);
+
+        let rows = self.fetch(q).await?;
+
+        let mut insights = Vec::new();
+        for row in rows {
+            let node: Node = row.get("i")?;
+            insights.push(Insight::from_node(&node)?);
+        }
+
+        Ok(insights)
+    }
+
+    /// Increment usage count for an insight
+    pub async fn increment_insight_usage(&self, insight_id: &str) -> Result<()> {
+        let q = query(
+            r#"
+            MATCH (i:Insight {id: $id})
+            SET i.usage_count = i.usage_count + 1
+            "#,
+        )
+        .param("id", insight_id);
+
+        self.run(q).await
+    }
+
+    /// Search insights by description
+    pub async fn search_insights(&self, search_term: &str, limit: i64) -> Result<Vec<Insight>> {
+        let q = query(
+            r#"
+            MATCH (i:Insight)
+            WHERE i.description CONTAINS $search_term
+            RETURN i
+            ORDER BY i.usage_count DESC
+            LIMIT $limit
+            "#,
+        )
+        .param("search_term", search_term)
+        .param("limit", limit);
+
+        let rows = self.fetch(q).await?;
+
+        let mut insights = Vec::new();
+        for row in rows {
+            let node: Node = row.get("i")?;
+            insights.push(Insight::from_node(&node)?);
+        }
+
+        Ok(insights)
+    }
+
+    // =========================================================================
+    // RULE OPERATIONS
+    // =========================================================================
+
+    /// Create a new rule
+    pub async fn create_rule(&self, rule: &Rule, project_path: Option<&str>) -> Result<String> {
+        let rule_id = rule
+            .id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        let mut rule = rule.clone();
+        rule.id = Some(rule_id.clone());
+
+        // Links to the project if project-scoped
+        self.write(GraphMutation::CreateRule {
+            rule,
+            project_path: project_path.map(String::from),
+        })
+        .await?;
+        Ok(rule_id)
+    }
+
+    /// Get rules by scope
+    pub async fn get_rules_by_scope(&self, scope: &str, project_path: Option<&str>) -> Result<Vec<Rule>> {
+        let q = if scope == "project" && project_path.is_some() {
+            query(
+                r#"
+                MATCH (r:Rule {scope: 'project'})-[:APPLIES_TO]->(p:Project {path: $project_path})
+                WHERE r.enabled = true
+                RETURN r
+                ORDER BY r.created_at DESC
+                "#,
+            )
+            .param("project_path", project_path.unwrap())
+        } else {
+            query(
+                r#"
+                MATCH (r:Rule {scope: $scope})
+                WHERE r.enabled = true
+                RETURN r
+                ORDER BY r.created_at DESC
+                "#,
+            )
+            .param("scope", scope)
+        };
+
+        let rows = self.fetch(q).await?;
+
+        let mut rules = Vec::new();
+        for row in rows {
+            let node: Node = row.get("r")?;
+            rules.push(Rule::from_node(&node)?);
+        }
+
+        Ok(rules)
+    }
+
+    /// Get all enabled rules (global + project-specific)
+    pub async fn get_enabled_rules(&self, project_path: &str) -> Result<Vec<Rule>> {
+        let q = query(
+            r#"
+            MATCH (r:Rule)
+            WHERE r.enabled = true
+            AND (r.scope = 'global'
+                 OR (r.scope = 'project' AND EXISTS {
+                     MATCH (r)-[:APPLIES_TO]->(p:Project {path: $project_path})
+                 }))
+            RETURN r
+            ORDER BY r.scope, r.created_at DESC
+            "#,
+        )
+        .param("project_path", project_path);
+
+        let rows = self.fetch(q).await?;
+
+        let mut rules = Vec::new();
+        for row in rows {
+            let node: Node = row.get("r")?;
+            rules.push(Rule::from_node(&node)?);
+        }
+
+        Ok(rules)
+    }
+
+    /// Toggle rule enabled status
+    pub async fn toggle_rule(&self, rule_id: &str, enabled: bool) -> Result<()> {
+        self.write(GraphMutation::ToggleRule {
+            rule_id: rule_id.to_string(),
+            enabled,
+        })
+        .await
+    }
+
+    /// Increment triggered count for a rule
+    pub async fn increment_rule_triggered(&self, rule_id: &str) -> Result<()> {
+        let q = query(
+            r#"
+            MATCH (r:Rule {id: $id})
+            SET r.triggered_count = r.triggered_count + 1
+            "#,
+        )
+        .param("id", rule_id);
+
+        self.run(q).await
+    }
+
+    /// Link a rule to an insight it was derived from
+    pub async fn link_rule_to_insight(&self, rule_id: &str, insight_id: &str) -> Result<()> {
+        let q = query(
+            r#"
+            MATCH (r:Rule {id: $rule_id}), (i:Insight {id: $insight_id})
+            MERGE (r)-[:DERIVED_FROM]->(i)
+            "#,
+        )
+        .param("rule_id", rule_id)
+        .param("insight_id", insight_id);
+
+        self.run(q).await
+    }
+
+    // =========================================================================
+    // STATISTICS
+    // =========================================================================
+
+    /// Get project statistics
+    pub async fn get_project_stats(&self, project_path: &str) -> Result<ProjectStats> {
+        let q = query(
+            r#"
+            MATCH (p:Project {path: $project_path})
+            OPTIONAL MATCH (f:Feature)-[:BELONGS_TO]->(p)
+            WITH p,
+                 count(f) as total,
+                 sum(CASE WHEN f.status = 'complete' THEN 1 ELSE 0 END) as completed,
+                 sum(CASE WHEN f.status = 'in_progress' THEN 1 ELSE 0 END) as in_progress
+            OPTIONAL MATCH (s:Session {status: 'active'})-[:IN_PROJECT]->(p)
+            WITH total, completed, in_progress, count(s) as active_sessions
+            RETURN total, completed, in_progress, active_sessions
+            "#,
+        )
+        .param("project_path", project_path);
+
+        let rows = self.fetch(q).await?;
+
+        if let Some(row) = rows.into_iter().next() {
+            let total: i64 = row.get("total")?;
+            let completed: i64 = row.get("completed")?;
+            let in_progress: i64 = row.get("in_progress")?;
+            let active_sessions: i64 = row.get("active_sessions")?;
+
+            Ok(ProjectStats {
+                total: total as i32,
+                completed: completed as i32,
+                in_progress: in_progress as i32,
+                percentage: if total > 0 {
+                    (completed as f64 / total as f64 * 100.0) as i32
+                } else {
+                    0
+                },
+                active_sessions: active_sessions as i32,
+            })
+        } else {
+            Ok(ProjectStats::default())
+        }
+    }
+}
+
+/// Returned when an operation needs a live graph connection and there is none
+#[derive(Debug)]
+pub struct NotConnected;
+
+impl std::fmt::Display for NotConnected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Not connected to graph database")
+    }
+}
+
+impl std::error::Error for NotConnected {}
+
+/// Returned when an agent status write is held back by a human lock on the
+/// feature's status
+#[derive(Debug)]
+pub struct StatusLocked(pub String);
+
+impl std::fmt::Display for StatusLocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Feature {} status is locked by a human edit", self.0)
+    }
+}
+
+impl std::error::Error for StatusLocked {}
+
+/// Returned when a status write made against an older copy of the feature
+/// meets a newer graph edit
+#[derive(Debug)]
+pub struct FeatureChanged(pub String);
+
+impl std::fmt::Display for FeatureChanged {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Feature {} changed in the graph since the edit was made", self.0)
+    }
+}
+
+impl std::error::Error for FeatureChanged {}
+
+/// Whether an error means the graph database is unreachable (no connection
+/// or a broken one), so the caller should report it as unavailable
+pub fn is_unavailable_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<NotConnected>()) || is_connection_error(err)
+}
+
+/// Whether an error means the Bolt connection itself is unusable, as opposed
+/// to a query or data error
+fn is_connection_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<neo4rs::Error>(),
+            Some(neo4rs::Error::IOError { .. } | neo4rs::Error::ConnectionError)
+        )
+    })
+}
+
+/// Cypher matching `f` by `$id` and deciding whether a status write may land:
+/// `unlocked` unless an agent meets an unexpired human status lock, `current`
+/// unless the feature changed after `$expected` (epoch micros, null skips it)
+fn status_write_guard(dialect: GraphDialect) -> String {
+    format!(
+        r#"
+        MATCH (f:Feature {{id: $id}})
+        OPTIONAL MATCH (f)-[:HAS_LOCK]->(l:FieldLock {{field: 'status'}})
+        WITH f,
+            $human OR l IS NULL OR (l.until IS NOT NULL AND l.until < $now) AS unlocked,
+            $expected IS NULL OR coalesce({}, 0) <= $expected AS current
+        "#,
+        dialect.epoch_micros("coalesce(f.updated_at, f.created_at)")
+    )
+}
+
+/// During replay, drop what is left of an offline edit whose status write
+/// lost to a newer graph edit: its status history and its status lock
+fn without_conflicted_status(mutation: GraphMutation, conflicted: &HashSet<String>) -> Option<GraphMutation> {
+    match mutation {
+        GraphMutation::RecordStatusChange { change } if conflicted.contains(&change.feature_id) => None,
+        GraphMutation::LockFields { feature_id, fields, until } if conflicted.contains(&feature_id) => {
+            let fields: Vec<String> = fields.into_iter().filter(|field| field != "status").collect();
+            (!fields.is_empty()).then_some(GraphMutation::LockFields { feature_id, fields, until })
+        }
+        mutation => Some(mutation),
+    }
+}
+
+// =============================================================================
+// DATA MODELS
+// =============================================================================
+
+/// A graph write, serializable so it can be queued in the SQLite outbox
+/// while the graph database is unreachable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum GraphMutation {
+    UpsertProject {
+        project: Project,
+    },
+    EnsureProject {
+        project: Project,
+    },
+    CreateFeature {
+        id: String,
+        project_path: String,
+        description: String,
+        category: String,
+        status: String,
+        priority: i64,
+        steps: Vec<String>,
+    },
+    // `human` writes go through a human status lock; entries queued before
+    // it existed replay as agent writes. `expected_changed_at` refuses the
+    // write if the feature changed after the edit was made.
+    UpdateFeatureStatus {
+        feature_id: String,
+        status: String,
+        #[serde(default)]
+        human: bool,
+        #[serde(default)]
+        expected_changed_at: Option<i64>,
+    },
+    ActivateFeature {
+        feature_id: String,
+        #[serde(default)]
+        human: bool,
+    },
+    CompleteFeature {
+        feature_id: String,
+        #[serde(default)]
+        human: bool,
+        #[serde(default)]
+        expected_changed_at: Option<i64>,
+    },
+    DeleteFeature {
+        feature_id: String,
+    },
+    LockFields {
+        feature_id: String,
+        fields: Vec<String>,
+        until: Option<String>,
+    },
+    ReleaseFieldLocks {
+        feature_id: String,
+        fields: Option<Vec<String>>,
+    },
+    RecordStatusChange {
+        change: StatusChange,
+    },
+    RecordEvent {
+        event: Event,
+        session_id: String,
+    },
+    LinkEventToFeature {
+        event_id: String,
+        feature_id: String,
+    },
+    StartSession {
+        session_id: String,
+        agent: String,
+        project_path: String,
+    },
+    EndSession {
+        session_id: String,
+    },
+    UpdateSessionActivity {
+        session_id: String,
+    },
+    RecordInsight {
+        insight: Insight,
+        event_id: Option<String>,
+    },
+    CreateRule {
+        rule: Rule,
+        project_path: Option<String>,
+    },
+    ToggleRule {
+        rule_id: String,
+        enabled: bool,
+    },
+}
+
+impl GraphMutation {
+    /// Operation name recorded alongside the outbox entry
+    pub fn operation(&self) -> &'static str {
+        match self {
+            GraphMutation::UpsertProject { .. } => "upsert_project",
+            GraphMutation::EnsureProject { .. } => "ensure_project",
+            GraphMutation::CreateFeature { .. } => "create_feature",
+            GraphMutation::UpdateFeatureStatus { .. } => "update_feature_status",
+            GraphMutation::ActivateFeature { .. } => "activate_feature",
+            GraphMutation::CompleteFeature { .. } => "complete_feature",
+            GraphMutation::DeleteFeature { .. } => "delete_feature",
+            GraphMutation::LockFields { .. } => "lock_fields",
+            GraphMutation::ReleaseFieldLocks { .. } => "release_field_locks",
+            GraphMutation::RecordStatusChange { .. } => "record_status_change",
+            GraphMutation::RecordEvent { .. } => "record_event",
+            GraphMutation::LinkEventToFeature { .. } => "link_event_to_feature",
+            GraphMutation::StartSession { .. } => "start_session",
+            GraphMutation::EndSession { .. } => "end_session",
+            GraphMutation::UpdateSessionActivity { .. } => "update_session_activity",
+            GraphMutation::RecordInsight { .. } => "record_insight",
+            GraphMutation::CreateRule { .. } => "create_rule",
+            GraphMutation::ToggleRule { .. } => "toggle_rule",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub path: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub settings: serde_json::Value,
+}
+
+impl Project {
+    /// A new project with defaults derived from its path
+    pub fn from_path(path: &str) -> Self {
+        let name = std::path::Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            path: path.to_string(),
+            name,
+            description: None,
+            created_at: None,
+            updated_at: None,
+            settings: serde_json::json!({}),
+        }
+    }
+
+    fn from_node(node: &Node) -> Result<Self> {
+        Ok(Self {
+            id: node.get("id")?,
+            path: node.get("path")?,
+            name: node.get("name")?,
+            description: node.get("description").ok(),
+            created_at: node.get::<String>("created_at").ok(),
+            updated_at: node.get::<String>("updated_at").ok(),
+            settings: serde_json::from_str(&node.get::<String>("settings").unwrap_or_default())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Feature {
+    pub id: Option<String>,
+    pub description: String,
+    pub category: String,
+    // Graph DB uses "status" but frontend expects "passes"/"inProgress"
+    #[serde(skip_serializing)]
+    pub status: String,
+    // Computed from status for frontend compatibility
+    pub passes: bool,
+    pub in_progress: bool,
+    pub priority: Option<i32>,
+    pub steps: Option<Vec<String>>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub work_count: Option<i32>,
+    #[serde(rename = "agent")]
+    pub assigned_agent: Option<String>,
+    // Project path (populated by caller)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+}
+
+impl Feature {
+    fn from_node(node: &Node) -> Result<Self> {
+        let status: String = node.get("status")?;
+        Ok(Self {
+            id: node.get("id").ok(),
+            description: node.get("description")?,
+            category: node.get("category")?,
+            passes: status == "complete",
+            in_progress: status == "in_progress",
+            status,
+            priority: node.get::<i64>("priority").ok().map(|p| p as i32),
+            steps: node.get::<Vec<String>>("steps").ok(),
+            created_at: node.get::<String>("created_at").ok(),
+            updated_at: node.get::<String>("updated_at").ok(),
+            completed_at: node.get::<String>("completed_at").ok(),
+            work_count: node.get::<i64>("work_count").ok().map(|w| w as i32),
+            assigned_agent: node.get("assigned_agent").ok(),
+            project_dir: None, // Set by caller
+        })
+    }
+}
+
+/// A feature moving between pending, in_progress and complete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChange {
+    pub id: String,
+    pub feature_id: String,
+    pub from_status: String,
+    pub to_status: String,
+    /// "human", "agent", "graph" or "file"
+    pub source: String,
+    pub agent: Option<String>,
+    pub session_id: Option<String>,
+    /// RFC 3339
+    pub changed_at: String,
+}
+
+/// Features changed since a sync watermark
+#[derive(Debug, Clone, Default)]
+pub struct FeatureChanges {
+    /// Changed features with their `updated_at` (microseconds since epoch)
+    pub features: Vec<(Feature, i64)>,
+    /// Latest `updated_at` seen (microseconds since epoch); the next `since`
+    pub watermark: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub id: Option<String>,
+    pub event_type: String,
+    pub tool_name: Option<String>,
+    pub payload: Option<serde_json::Value>,
+    pub summary: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub timestamp: Option<String>,
+    pub success: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_input: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    pub source_agent: Option<String>,
+    pub session_id: Option<String>,
+    // Enriched fields (populated by queries)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "projectDir")]
+    pub project_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_description: Option<String>,
+}
+
+impl Event {
+    fn from_node(node: &Node) -> Result<Self> {
+        Ok(Self {
+            id: node.get("id").ok(),
+            event_type: node.get("event_type")?,
+            tool_name: node.get("tool_name").ok(),
+            payload: serde_json::from_str(&node.get::<String>("payload").unwrap_or_default()).ok(),
+            summary: node.get("summary").ok(),
+            timestamp: node.get::<String>("timestamp").ok(),
+            success: node.get("success").ok(),
+            tool_input: serde_json::from_str(&node.get::<String>("tool_input").unwrap_or_default()).ok(),
+            exit_code: node.get("exit_code").ok(),
+            duration_ms: node.get("duration_ms").ok(),
+            source_agent: node.get("source_agent").ok(),
+            session_id: None, // Populated from Session relationship
+            // These are populated by the caller after from_node
+            project_path: None,
+            feature_id: None,
+            feature_description: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub id: String,
+    pub agent: String,
+    pub status: String,
+    pub started_at: Option<String>,
+    pub ended_at: Option<String>,
+    pub last_activity: Option<String>,
+    pub event_count: Option<i32>,
+    pub is_subagent: Option<bool>,
+}
+
+impl Session {
+    fn from_node(node: &Node) -> Result<Self> {
+        Ok(Self {
+            id: node.get("id")?,
+            agent: node.get("agent")?,
+            status: node.get("status")?,
+            started_at: node.get::<String>("started_at").ok(),
+            ended_at: node.get::<String>("ended_at").ok(),
+            last_activity: node.get::<String>("last_activity").ok(),
+            event_count: node.get::<i64>("event_count").ok().map(|c| c as i32),
+            is_subagent: node.get("is_subagent").ok(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStats {
+    pub total: i32,
+    pub completed: i32,
+    pub in_progress: i32,
+    pub percentage: i32,
+    pub active_sessions: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Insight {
+    pub id: Option<String>,
+    pub description: String,
+    pub pattern_type: String, // solution, anti_pattern, best_practice, tool_usage
+    pub tags: Option<Vec<String>>,
+    pub created_at: Option<String>,
+    pub usage_count: Option<i32>,
+    pub effectiveness_score: Option<f64>,
+}
+
+impl Insight {
+    fn from_node(node: &Node) -> Result<Self> {
+        Ok(Self {
+            id: node.get("id").ok(),
+            description: node.get("description")?,
+            pattern_type: node.get("pattern_type")?,
+            tags: node.get::<Vec<String>>("tags").ok(),
+            created_at: node.get::<String>("created_at").ok(),
+            usage_count: node.get::<i64>("usage_count").ok().map(|c| c as i32),
+            effectiveness_score: node.get::<f64>("effectiveness_score").ok(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub id: Option<String>,
+    pub name: String,
+    pub description: String,
+    pub trigger: serde_json::Value, // RuleTrigger serialized
+    pub action: serde_json::Value,  // RuleAction serialized
+    pub scope: String,              // global, project, feature
+    pub enforcement: String,        // hook, prompt_inject, reminder
+    pub enabled: Option<bool>,
+    pub created_at: Option<String>,
+    pub triggered_count: Option<i32>,
+    pub source_instruction_count: Option<i32>,
+}
+
+impl Rule {
+    fn from_node(node: &Node) -> Result<Self> {
+        Ok(Self {
+            id: node.get("id").ok(),
+            name: node.get("name")?,
+            description: node.get("description")?,
+            trigger: serde_json::from_str(&node.get::<String>("trigger").unwrap_or_default())
+                .unwrap_or_default(),
+            action: serde_json::from_str(&node.get::<String>("action").unwrap_or_default())
+                .unwrap_or_default(),
+            scope: node.get("scope")?,
+            enforcement: node.get("enforcement")?,
+            enabled: node.get("enabled").ok(),
+            created_at: node.get::<String>("created_at").ok(),
+            triggered_count: node.get::<i64>("triggered_count").ok().map(|c| c as i32),
+            source_instruction_count: node.get::<i64>("source_instruction_count").ok().map(|c| c as i32),
+        })
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = GraphDbConfig::default();
+        assert_eq!(config.uri, "bolt://localhost:7687");
+        assert_eq!(config.user, "ijoka");
+    }
+
+    #[tokio::test]
+    async fn test_writes_queue_in_outbox_while_disconnected() {
+        let database = Arc::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        let graph = GraphDb::new().with_outbox(Arc::clone(&database));
+
+        graph.start_session("s1", "claude-code", "/tmp/project").await.unwrap();
+        graph.end_session("s1").await.unwrap();
+
+        let pending = database.get_pending_outbox(10).unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].operation, "start_session");
+        assert_eq!(pending[1].operation, "end_session");
+
+        let mutation: GraphMutation = serde_json::from_str(&pending[0].payload).unwrap();
+        assert!(matches!(mutation, GraphMutation::StartSession { ref session_id, .. } if session_id == "s1"));
+    }
+
+    #[tokio::test]
+    async fn test_queued_auto_project_is_create_only() {
+        let database = Arc::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        let graph = GraphDb::new().with_outbox(Arc::clone(&database));
+
+        graph.ensure_project(&Project::from_path("/tmp/project")).await.unwrap();
+
+        let pending = database.get_pending_outbox(10).unwrap();
+        assert_eq!(pending[0].operation, "ensure_project");
+        let mutation: GraphMutation = serde_json::from_str(&pending[0].payload).unwrap();
+        assert!(matches!(mutation, GraphMutation::EnsureProject { ref project } if project.path == "/tmp/project"));
+    }
+
+    #[tokio::test]
+    async fn test_queued_status_writes_carry_the_lock_bypass() {
+        let database = Arc::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        let graph = GraphDb::new().with_outbox(Arc::clone(&database));
+
+        graph.update_feature_status("f1", "in_progress", UpdateSource::Agent, None).await.unwrap();
+        graph.complete_feature("f1", UpdateSource::Human, Some(42)).await.unwrap();
+
+        let pending = database.get_pending_outbox(10).unwrap();
+        let agent: GraphMutation = serde_json::from_str(&pending[0].payload).unwrap();
+        assert!(matches!(agent, GraphMutation::UpdateFeatureStatus { human: false, .. }));
+        let human: GraphMutation = serde_json::from_str(&pending[1].payload).unwrap();
+        assert!(matches!(
+            human,
+            GraphMutation::CompleteFeature { human: true, expected_changed_at: Some(42), .. }
+        ));
+
+        // Entries queued before the flag existed are checked against the lock
+        let legacy: GraphMutation =
+            serde_json::from_str(r#"{"op":"complete_feature","feature_id":"f1"}"#).unwrap();
+        assert!(matches!(
+            legacy,
+            GraphMutation::CompleteFeature { human: false, expected_changed_at: None, .. }
+        ));
+    }
+
+    #[test]
+    fn test_status_lock_is_not_a_connection_error() {
+        let err = anyhow::Error::new(StatusLocked("f1".to_string())).context("push failed");
+        assert!(err.is::<StatusLocked>());
+        assert!(!is_unavailable_error(&err));
+    }
+
+    #[test]
+    fn test_graph_schema_versions_are_sequential() {
+        for schema in [MEMGRAPH_SCHEMA, NEO4J_SCHEMA] {
+            for (i, step) in schema.iter().enumerate() {
+                assert_eq!(step.version, i as i64 + 1, "{}", step.description);
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_connection_error() {
+        let err = anyhow::Error::from(neo4rs::Error::ConnectionError).context("query failed");
+        assert!(is_connection_error(&err));
+        assert!(!is_connection_error(&anyhow::anyhow!("Not connected to graph database")));
+        assert!(!is_connection_error(&neo4rs::Error::ConversionError.into()));
+    }
+
+    #[tokio::test]
+    async fn test_writes_fail_without_outbox_while_disconnected() {
+        let graph = GraphDb::new();
+        assert!(graph.end_session("s1").await.is_err());
+    }
+}
@@ -0,0 +1,285 @@
+//! Claude Code Hook Events
+//!
+//! Typed model of the hook events agents post to the ingestion server. Each
+//! known event type has a payload struct; unrecognised types are kept as
+//! `Unknown` so new hook kinds are still recorded. Payload fields use Claude
+//! Code's snake_case names and also accept camelCase.
+
+use crate::db::EventDetails;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Longest summary kept for an event (prompts can be very long)
+const SUMMARY_MAX_CHARS: usize = 200;
+
+#[derive(Debug, Clone)]
+pub enum HookEvent {
+    SessionStart(SessionStartPayload),
+    UserPromptSubmit(UserPromptSubmitPayload),
+    PreToolUse(ToolUsePayload),
+    PostToolUse(ToolResultPayload),
+    Notification(NotificationPayload),
+    Stop(StopPayload),
+    SubagentStop(StopPayload),
+    PreCompact(PreCompactPayload),
+    SessionEnd(SessionEndPayload),
+    Unknown(String),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionStartPayload {
+    /// startup, resume, clear or compact
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserPromptSubmitPayload {
+    pub prompt: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ToolUsePayload {
+    #[serde(default, alias = "toolName")]
+    pub tool_name: Option<String>,
+    #[serde(default, alias = "toolInput")]
+    pub tool_input: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ToolResultPayload {
+    #[serde(default, alias = "toolName")]
+    pub tool_name: Option<String>,
+    #[serde(default, alias = "toolInput")]
+    pub tool_input: Option<Value>,
+    #[serde(default, alias = "toolResponse")]
+    pub tool_response: Option<Value>,
+    #[serde(default, alias = "exitCode")]
+    pub exit_code: Option<i64>,
+    #[serde(default)]
+    pub success: Option<bool>,
+    #[serde(default, alias = "durationMs")]
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationPayload {
+    pub message: String,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StopPayload {
+    #[serde(default, alias = "stopHookActive")]
+    pub stop_hook_active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PreCompactPayload {
+    /// manual or auto
+    #[serde(default)]
+    pub trigger: Option<String>,
+    #[serde(default, alias = "customInstructions")]
+    pub custom_instructions: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionEndPayload {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl HookEvent {
+    /// Validate an incoming event. `tool_name` is the top-level field, which
+    /// takes precedence over one in the payload. A missing payload is treated
+    /// as an empty object.
+    pub fn parse(event_type: &str, tool_name: Option<&str>, payload: Option<&Value>) -> Result<Self, String> {
+        let empty = Value::Object(Default::default());
+        let payload = payload.filter(|p| !p.is_null()).unwrap_or(&empty);
+
+        let event = match event_type {
+            "SessionStart" => HookEvent::SessionStart(parse_payload(event_type, payload)?),
+            "UserPromptSubmit" => HookEvent::UserPromptSubmit(parse_payload(event_type, payload)?),
+            "PreToolUse" => {
+                let mut p: ToolUsePayload = parse_payload(event_type, payload)?;
+                p.tool_name = require_tool_name(event_type, tool_name, p.tool_name)?;
+                HookEvent::PreToolUse(p)
+            }
+            "PostToolUse" => {
+                let mut p: ToolResultPayload = parse_payload(event_type, payload)?;
+                p.tool_name = require_tool_name(event_type, tool_name, p.tool_name)?;
+                HookEvent::PostToolUse(p)
+            }
+            "Notification" => HookEvent::Notification(parse_payload(event_type, payload)?),
+            "Stop" => HookEvent::Stop(parse_payload(event_type, payload)?),
+            "SubagentStop" => HookEvent::SubagentStop(parse_payload(event_type, payload)?),
+            "PreCompact" => HookEvent::PreCompact(parse_payload(event_type, payload)?),
+            "SessionEnd" => HookEvent::SessionEnd(parse_payload(event_type, payload)?),
+            "" => return Err("eventType must not be empty".to_string()),
+            other => HookEvent::Unknown(other.to_string()),
+        };
+
+        Ok(event)
+    }
+
+    pub fn event_type(&self) -> &str {
+        match self {
+            HookEvent::SessionStart(_) => "SessionStart",
+            HookEvent::UserPromptSubmit(_) => "UserPromptSubmit",
+            HookEvent::PreToolUse(_) => "PreToolUse",
+            HookEvent::PostToolUse(_) => "PostToolUse",
+            HookEvent::Notification(_) => "Notification",
+            HookEvent::Stop(_) => "Stop",
+            HookEvent::SubagentStop(_) => "SubagentStop",
+            HookEvent::PreCompact(_) => "PreCompact",
+            HookEvent::SessionEnd(_) => "SessionEnd",
+            HookEvent::Unknown(event_type) => event_type,
+        }
+    }
+
+    pub fn tool_name(&self) -> Option<&str> {
+        match self {
+            HookEvent::PreToolUse(p) => p.tool_name.as_deref(),
+            HookEvent::PostToolUse(p) => p.tool_name.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// One-line human readable description, if the event carries one
+    pub fn summary(&self) -> Option<String> {
+        let summary = match self {
+            HookEvent::SessionStart(p) => match &p.source {
+                Some(source) => format!("Session started ({})", source),
+                None => "Session started".to_string(),
+            },
+            HookEvent::UserPromptSubmit(p) => p.prompt.clone(),
+            HookEvent::Notification(p) => match &p.title {
+                Some(title) => format!("{}: {}", title, p.message),
+                None => p.message.clone(),
+            },
+            HookEvent::Stop(p) | HookEvent::SubagentStop(p) => {
+                let who = if matches!(self, HookEvent::Stop(_)) { "Agent" } else { "Subagent" };
+                if p.stop_hook_active == Some(true) {
+                    format!("{} stopped (stop hook active)", who)
+                } else {
+                    format!("{} stopped", who)
+                }
+            }
+            HookEvent::PreCompact(p) => {
+                let trigger = p.trigger.as_deref().unwrap_or("auto");
+                match &p.custom_instructions {
+                    Some(instructions) if !instructions.is_empty() => {
+                        format!("Compacting context ({}): {}", trigger, instructions)
+                    }
+                    _ => format!("Compacting context ({})", trigger),
+                }
+            }
+            HookEvent::SessionEnd(p) => match &p.reason {
+                Some(reason) => format!("Session ended ({})", reason),
+                None => "Session ended".to_string(),
+            },
+            HookEvent::PreToolUse(_) | HookEvent::PostToolUse(_) | HookEvent::Unknown(_) => return None,
+        };
+
+        Some(summary.chars().take(SUMMARY_MAX_CHARS).collect())
+    }
+
+    /// Typed fields stored as event columns and graph properties
+    pub fn details(&self) -> EventDetails {
+        match self {
+            HookEvent::PreToolUse(p) => EventDetails {
+                tool_input: p.tool_input.as_ref().map(Value::to_string),
+                ..Default::default()
+            },
+            HookEvent::PostToolUse(p) => {
+                // Fall back to what the tool itself reported
+                let response = p.tool_response.as_ref();
+                let response_field = |names: &[&str]| {
+                    response.and_then(|r| names.iter().find_map(|name| r.get(*name)))
+                };
+
+                let exit_code = p
+                    .exit_code
+                    .or_else(|| response_field(&["exit_code", "exitCode"]).and_then(Value::as_i64));
+                let success = p
+                    .success
+                    .or_else(|| response_field(&["success"]).and_then(Value::as_bool))
+                    .or(exit_code.map(|code| code == 0));
+
+                EventDetails {
+                    tool_input: p.tool_input.as_ref().map(Value::to_string),
+                    exit_code,
+                    success,
+                    duration_ms: p
+                        .duration_ms
+                        .or_else(|| response_field(&["duration_ms", "durationMs"]).and_then(Value::as_i64)),
+                    summary: None,
+                }
+            }
+            _ => EventDetails {
+                summary: self.summary(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+fn parse_payload<T: DeserializeOwned>(event_type: &str, payload: &Value) -> Result<T, String> {
+    if !payload.is_object() {
+        return Err(format!("{} payload must be a JSON object", event_type));
+    }
+    T::deserialize(payload).map_err(|e| format!("Invalid {} payload: {}", event_type, e))
+}
+
+fn require_tool_name(
+    event_type: &str,
+    top_level: Option<&str>,
+    in_payload: Option<String>,
+) -> Result<Option<String>, String> {
+    top_level
+        .map(String::from)
+        .or(in_payload)
+        .filter(|name| !name.is_empty())
+        .map(Some)
+        .ok_or_else(|| format!("{} requires toolName", event_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unknown_event_types_are_kept() {
+        let event = HookEvent::parse("SomethingNew", None, Some(&json!({"any": "thing"}))).unwrap();
+        assert!(matches!(event, HookEvent::Unknown(_)));
+        assert_eq!(event.event_type(), "SomethingNew");
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        assert!(HookEvent::parse("PreToolUse", None, None).is_err());
+        assert!(HookEvent::parse("UserPromptSubmit", None, Some(&json!({}))).is_err());
+        assert!(HookEvent::parse("Notification", None, Some(&json!("text"))).is_err());
+        assert!(HookEvent::parse("", None, None).is_err());
+    }
+
+    #[test]
+    fn test_post_tool_use_details() {
+        let payload = json!({
+            "tool_input": {"command": "cargo test"},
+            "tool_response": {"exitCode": 101},
+            "durationMs": 1500
+        });
+        let event = HookEvent::parse("PostToolUse", Some("Bash"), Some(&payload)).unwrap();
+        assert_eq!(event.tool_name(), Some("Bash"));
+
+        let details = event.details();
+        assert_eq!(details.tool_input.as_deref(), Some(r#"{"command":"cargo test"}"#));
+        assert_eq!(details.exit_code, Some(101));
+        assert_eq!(details.success, Some(false));
+        assert_eq!(details.duration_ms, Some(1500));
+    }
+}
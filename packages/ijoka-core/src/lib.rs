@@ -0,0 +1,27 @@
+//! Ijoka Core
+//!
+//! Everything that keeps Ijoka's data flowing, independent of the desktop UI:
+//! the SQLite cache, the graph database client, the hook ingestion server,
+//! the MCP server, the file watcher and the graph -> SQLite sync loops.
+//!
+//! `Daemon` wires these together. The `ijokad` binary runs it headless; the
+//! desktop app embeds it (or attaches to a running `ijokad`) and forwards
+//! `EventSink` callbacks to its webview.
+
+pub mod auth;
+pub mod daemon;
+pub mod db;
+pub mod events;
+pub mod graph_db;
+pub mod hook_event;
+pub mod mcp;
+pub mod migrations;
+pub mod pool;
+pub mod retention;
+pub mod server;
+pub mod store;
+pub mod sync;
+pub mod watcher;
+
+pub use daemon::Daemon;
+pub use events::{EventSink, LogSink};
@@ -0,0 +1,471 @@
+//! MCP Server
+//!
+//! Model Context Protocol endpoint that lets agents (Claude Code, Gemini, ...)
+//! manage the board directly instead of editing feature_list.json. Speaks
+//! JSON-RPC 2.0 over two transports:
+//!
+//! - stdio: `ijoka mcp`, one JSON message per line
+//! - streamable HTTP: `POST /mcp` on the ingestion server (bearer token required)
+//!
+//! Tools are backed by the graph database, with feature reads falling back to
+//! the SQLite cache. Feature status changes are agent updates, so they respect
+//! human overrides like every other agent write.
+
+use crate::db::{Database, FeatureUpdate, UpdateAuthor, UpdateOutcome, UpdateSource};
+use crate::graph_db::{self, GraphDb};
+use crate::store::DataStore;
+use anyhow::{anyhow, bail, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// Protocol revisions we can speak, newest first
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// MCP request handler shared by the stdio and HTTP transports
+pub struct McpServer {
+    graph: Arc<GraphDb>,
+    sqlite: Arc<Database>,
+    /// Authenticated agent, when served over HTTP
+    agent: Option<String>,
+}
+
+impl McpServer {
+    pub fn new(graph: Arc<GraphDb>, sqlite: Arc<Database>) -> Self {
+        Self {
+            graph,
+            sqlite,
+            agent: None,
+        }
+    }
+
+    /// Attribute feature changes made through this server to `agent`
+    pub fn with_agent(mut self, agent: String) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+
+    /// Handle one raw JSON-RPC message. Returns the response to send back, or
+    /// None for notifications and responses, which get no reply.
+    pub async fn handle_message(&self, text: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // A response to a server request (we send none) or garbage
+            if message.get("id").is_some() && (message.get("result").is_some() || message.get("error").is_some()) {
+                return None;
+            }
+            let id = message.get("id").cloned().unwrap_or(Value::Null);
+            return Some(error_response(id, INVALID_REQUEST, "Expected a JSON-RPC request"));
+        };
+
+        // Notifications (no id) never get a response
+        let id = message.get("id").cloned()?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let response = match method {
+            "initialize" => Ok(initialize_result(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => self.call_tool(params).await,
+            other => Err((METHOD_NOT_FOUND, format!("Unknown method {}", other))),
+        };
+
+        Some(match response {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    async fn call_tool(&self, params: Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "tools/call requires a tool name".to_string()))?;
+        let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+        if !tool_definitions().iter().any(|tool| tool["name"] == name) {
+            return Err((INVALID_PARAMS, format!("Unknown tool {}", name)));
+        }
+
+        // Tool failures are reported in the result so the model can see them
+        Ok(match self.run_tool(name, arguments).await {
+            Ok(output) => json!({
+                "content": [{ "type": "text", "text": serde_json::to_string_pretty(&output).unwrap_or_default() }],
+                "isError": false,
+            }),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": e.to_string() }],
+                "isError": true,
+            }),
+        })
+    }
+
+    async fn run_tool(&self, name: &str, arguments: Value) -> Result<Value> {
+        let store = DataStore::new(&self.graph, &self.sqlite);
+
+        match name {
+            "list_features" => {
+                let args: ProjectArgs = parse_args(arguments)?;
+                Ok(json!(store.get_features(&args.project_dir).await?))
+            }
+            "get_active_feature" => {
+                let args: ProjectArgs = parse_args(arguments)?;
+                Ok(json!(self.graph.get_active_feature(&args.project_dir).await?))
+            }
+            "activate_feature" => {
+                let args: FeatureArgs = parse_args(arguments)?;
+                let update = FeatureUpdate {
+                    passes: Some(false),
+                    in_progress: Some(true),
+                    ..Default::default()
+                };
+                self.update_feature(&store, &args, update).await
+            }
+            "complete_feature" => {
+                let args: FeatureArgs = parse_args(arguments)?;
+                let update = FeatureUpdate {
+                    passes: Some(true),
+                    in_progress: Some(false),
+                    ..Default::default()
+                };
+                self.update_feature(&store, &args, update).await
+            }
+            "record_insight" => {
+                let args: RecordInsightArgs = parse_args(arguments)?;
+                let mut insight = graph_db::Insight {
+                    id: None,
+                    description: args.description,
+                    pattern_type: args.pattern_type,
+                    tags: args.tags,
+                    created_at: None,
+                    usage_count: Some(0),
+                    effectiveness_score: None,
+                };
+                insight.id = Some(self.graph.record_insight(&insight, args.event_id.as_deref()).await?);
+                Ok(json!(insight))
+            }
+            "search_insights" => {
+                let args: SearchInsightsArgs = parse_args(arguments)?;
+                let limit = args.limit.unwrap_or(10);
+                let insights = match args.tags.filter(|tags| !tags.is_empty()) {
+                    Some(tags) => self.graph.get_insights_by_tags(&tags, limit).await?,
+                    None => self.graph.search_insights(&args.query.unwrap_or_default(), limit).await?,
+                };
+                Ok(json!(insights))
+            }
+            "get_rules" => {
+                let args: RulesArgs = parse_args(arguments)?;
+                let rules = match args.project_dir.as_deref() {
+                    Some(project_dir) => self.graph.get_enabled_rules(project_dir).await?,
+                    None => self.graph.get_rules_by_scope("global", None).await?,
+                };
+                Ok(json!(rules))
+            }
+            other => bail!("Unknown tool {}", other),
+        }
+    }
+
+    /// Apply an agent status change and return the updated feature
+    async fn update_feature(&self, store: &DataStore<'_>, args: &FeatureArgs, update: FeatureUpdate) -> Result<Value> {
+        let belongs = |feature: &graph_db::Feature| feature.project_dir.as_deref() == Some(args.project_dir.as_str());
+
+        store
+            .get_cached_feature(&args.feature_id)
+            .await?
+            .filter(belongs)
+            .ok_or_else(|| anyhow!("Feature {} not found in {}", args.feature_id, args.project_dir))?;
+
+        let author = UpdateAuthor {
+            agent: self.agent.clone(),
+            session_id: args.session_id.clone(),
+        };
+        match store.update_feature(&args.feature_id, update, UpdateSource::Agent, author).await? {
+            UpdateOutcome::Applied(_) => {}
+            UpdateOutcome::Locked(fields) => bail!(
+                "Feature {} has fields locked by a human edit: {}",
+                args.feature_id,
+                fields.join(", ")
+            ),
+            UpdateOutcome::NotFound => bail!("Feature {} not found", args.feature_id),
+        }
+
+        Ok(json!(store.get_cached_feature(&args.feature_id).await?))
+    }
+}
+
+/// Serve MCP over stdin/stdout until stdin closes
+pub async fn serve_stdio(server: McpServer) -> std::io::Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = server.handle_message(&line).await {
+            let mut out = response.to_string();
+            out.push('\n');
+            stdout.write_all(out.as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Serve MCP over stdin/stdout against the shared database. Graph writes made
+/// while Memgraph is down are queued in the outbox for the daemon to replay.
+pub fn run_stdio() -> Result<(), Box<dyn std::error::Error>> {
+    let runtime = tokio::runtime::Runtime::new()?;
+
+    runtime.block_on(async {
+        let db_path = crate::db::get_standard_db_path();
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let database = Arc::new(Database::new(&db_path)?);
+
+        let graph = Arc::new(GraphDb::new().with_outbox(Arc::clone(&database)));
+        if let Err(e) = graph.connect().await {
+            tracing::warn!("Graph database unavailable, serving from the SQLite cache: {}", e);
+        }
+
+        serve_stdio(McpServer::new(graph, database)).await?;
+        Ok(())
+    })
+}
+
+fn initialize_result(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|v| PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "ijoka", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "Ijoka tracks this project's features. Use list_features and get_active_feature \
+                         to see what to work on, activate_feature before starting and complete_feature \
+                         when done, instead of editing feature_list.json.",
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn parse_args<T: DeserializeOwned>(arguments: Value) -> Result<T> {
+    serde_json::from_value(arguments).map_err(|e| anyhow!("Invalid arguments: {}", e))
+}
+
+#[derive(Deserialize)]
+struct ProjectArgs {
+    project_dir: String,
+}
+
+#[derive(Deserialize)]
+struct FeatureArgs {
+    project_dir: String,
+    feature_id: String,
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RecordInsightArgs {
+    description: String,
+    pattern_type: String,
+    tags: Option<Vec<String>>,
+    event_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SearchInsightsArgs {
+    query: Option<String>,
+    tags: Option<Vec<String>>,
+    limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct RulesArgs {
+    project_dir: Option<String>,
+}
+
+fn tool_definitions() -> Vec<Value> {
+    let project_dir = json!({ "type": "string", "description": "Absolute path of the project" });
+    let feature_args = json!({
+        "type": "object",
+        "properties": {
+            "project_dir": project_dir,
+            "feature_id": { "type": "string", "description": "Feature id from list_features" },
+            "session_id": { "type": "string", "description": "Current session id, recorded in the feature history" },
+        },
+        "required": ["project_dir", "feature_id"],
+    });
+
+    vec![
+        json!({
+            "name": "list_features",
+            "description": "List a project's features with their status",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "project_dir": project_dir },
+                "required": ["project_dir"],
+            },
+        }),
+        json!({
+            "name": "get_active_feature",
+            "description": "Get the feature currently in progress for a project, if any",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "project_dir": project_dir },
+                "required": ["project_dir"],
+            },
+        }),
+        json!({
+            "name": "activate_feature",
+            "description": "Mark a feature as in progress. Refused while a human holds a lock on its status.",
+            "inputSchema": feature_args,
+        }),
+        json!({
+            "name": "complete_feature",
+            "description": "Mark a feature as complete. Refused while a human holds a lock on its status.",
+            "inputSchema": feature_args,
+        }),
+        json!({
+            "name": "record_insight",
+            "description": "Record a reusable lesson learned while working",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "description": { "type": "string" },
+                    "pattern_type": {
+                        "type": "string",
+                        "enum": ["solution", "anti_pattern", "best_practice", "tool_usage"],
+                    },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "event_id": { "type": "string", "description": "Event the insight came from" },
+                },
+                "required": ["description", "pattern_type"],
+            },
+        }),
+        json!({
+            "name": "search_insights",
+            "description": "Find recorded insights by description text or tags",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Text the description contains" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "limit": { "type": "integer", "minimum": 1 },
+                },
+            },
+        }),
+        json!({
+            "name": "get_rules",
+            "description": "Get the enabled rules for a project (global rules only without project_dir)",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "project_dir": project_dir },
+            },
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::graph_feature;
+    use crate::db::GraphFeatureSync;
+    use std::path::Path;
+
+    fn server() -> McpServer {
+        let sqlite = Arc::new(Database::new(Path::new(":memory:")).unwrap());
+        McpServer::new(Arc::new(GraphDb::new()), sqlite)
+    }
+
+    #[tokio::test]
+    async fn test_protocol_messages() {
+        let server = server();
+
+        let init = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#)
+            .await
+            .unwrap();
+        assert_eq!(init["result"]["protocolVersion"], "2025-03-26");
+
+        let notification = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        assert!(server.handle_message(notification).await.is_none());
+
+        let tools = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#)
+            .await
+            .unwrap();
+        assert_eq!(tools["result"]["tools"].as_array().unwrap().len(), 7);
+
+        let unknown = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":3,"method":"resources/list"}"#)
+            .await
+            .unwrap();
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+
+        let garbage = server.handle_message("{not json").await.unwrap();
+        assert_eq!(garbage["error"]["code"], PARSE_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_feature_tools_use_cache_when_graph_is_offline() {
+        let server = server();
+        server
+            .sqlite
+            .sync_feature_from_graph(&GraphFeatureSync {
+                description: "Login page".to_string(),
+                ..graph_feature("f1", "/p")
+            })
+            .unwrap();
+
+        let call = |name: &str, arguments: Value| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": { "name": name, "arguments": arguments },
+            })
+            .to_string()
+        };
+
+        let listed = server
+            .handle_message(&call("list_features", json!({ "project_dir": "/p" })))
+            .await
+            .unwrap();
+        assert_eq!(listed["result"]["isError"], false);
+        assert!(listed["result"]["content"][0]["text"].as_str().unwrap().contains("Login page"));
+
+        // Wrong project is an error the model can see, not a protocol error
+        let missing = server
+            .handle_message(&call("complete_feature", json!({ "project_dir": "/other", "feature_id": "f1" })))
+            .await
+            .unwrap();
+        assert_eq!(missing["result"]["isError"], true);
+
+        let bad_args = server
+            .handle_message(&call("list_features", json!({})))
+            .await
+            .unwrap();
+        assert_eq!(bad_args["result"]["isError"], true);
+    }
+}
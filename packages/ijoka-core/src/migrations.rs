@@ -0,0 +1,513 @@
+//! SQLite Schema Migrations
+//!
+//! Numbered migrations keyed on `PRAGMA user_version`. Each pending migration
+//! runs in its own transaction together with the version bump, so a failure
+//! rolls back cleanly and is reported instead of leaving a half-migrated cache.
+//!
+//! Databases created before versioning start at user_version 0 and may already
+//! contain some of the columns below, so column additions check first.
+
+use rusqlite::{Connection, Transaction};
+
+/// A single schema or data migration
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub up: fn(&Transaction) -> Result<(), rusqlite::Error>,
+}
+
+/// All migrations in order. Never edit or reorder a released migration;
+/// append a new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create base tables",
+        up: create_base_tables,
+    },
+    Migration {
+        version: 2,
+        description: "Add events.feature_id",
+        up: |tx| {
+            add_column(tx, "events", "feature_id", "TEXT")?;
+            tx.execute_batch("CREATE INDEX IF NOT EXISTS idx_events_feature_id ON events(feature_id);")
+        },
+    },
+    Migration {
+        version: 3,
+        description: "Add feature steps and auto-completion columns",
+        up: |tx| {
+            add_column(tx, "features", "steps", "TEXT")?;
+            add_column(tx, "features", "work_count", "INTEGER DEFAULT 0")?;
+            add_column(tx, "features", "completion_criteria", "TEXT")
+        },
+    },
+    Migration {
+        version: 4,
+        description: "Add agent-managed feature state",
+        up: |tx| {
+            add_column(tx, "features", "confidence", "INTEGER")?;
+            add_column(tx, "features", "model", "TEXT")?;
+            add_column(tx, "features", "is_streaming", "INTEGER DEFAULT 0")?;
+            add_column(tx, "features", "retry_count", "INTEGER DEFAULT 0")?;
+            add_column(tx, "features", "token_cost", "INTEGER")?;
+            add_column(tx, "features", "has_error", "INTEGER DEFAULT 0")?;
+            add_column(tx, "features", "last_agent_update", "TEXT")
+        },
+    },
+    Migration {
+        version: 5,
+        description: "Add human override feature state",
+        up: |tx| {
+            add_column(tx, "features", "manual_priority", "TEXT")?;
+            add_column(tx, "features", "human_override_until", "TEXT")
+        },
+    },
+    Migration {
+        version: 6,
+        description: "Create graph outbox",
+        up: |tx| {
+            // Graph mutations queued while Memgraph is unreachable, replayed in id order
+            tx.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS graph_outbox (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    operation TEXT NOT NULL,
+                    payload TEXT NOT NULL,
+                    status TEXT DEFAULT 'pending',
+                    attempts INTEGER DEFAULT 0,
+                    last_error TEXT,
+                    created_at TEXT DEFAULT (datetime('now'))
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_graph_outbox_status ON graph_outbox(status, id);
+                "#,
+            )
+        },
+    },
+    Migration {
+        version: 7,
+        description: "Track graph-mirrored features",
+        up: |tx| {
+            // from_graph keeps reconciliation away from feature_list.json rows;
+            // graph_updated_at lets human edits detect unseen graph changes.
+            // Drop the sync watermark so a full resync fills both in.
+            add_column(tx, "features", "from_graph", "INTEGER DEFAULT 0")?;
+            add_column(tx, "features", "graph_updated_at", "INTEGER")?;
+            tx.execute("DELETE FROM config WHERE key = 'graph_sync_watermark'", [])?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 8,
+        description: "Normalize features.updated_at to SQLite datetime format",
+        up: |tx| {
+            // Older builds stored RFC 3339 timestamps, which sort inconsistently
+            // against datetime('now') values
+            tx.execute(
+                "UPDATE features SET updated_at = datetime(updated_at)
+                 WHERE datetime(updated_at) IS NOT NULL AND updated_at IS NOT datetime(updated_at)",
+                [],
+            )?;
+            tx.execute(
+                "UPDATE features SET updated_at = datetime('now') WHERE datetime(updated_at) IS NULL",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 9,
+        description: "Add events.idempotency_key",
+        up: |tx| {
+            // NULLs are distinct in a unique index, so keyless events are unaffected
+            add_column(tx, "events", "idempotency_key", "TEXT")?;
+            tx.execute_batch(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_events_idempotency_key ON events(idempotency_key);",
+            )
+        },
+    },
+    Migration {
+        version: 10,
+        description: "Add typed hook event columns",
+        up: |tx| {
+            add_column(tx, "events", "tool_input", "TEXT")?;
+            add_column(tx, "events", "exit_code", "INTEGER")?;
+            add_column(tx, "events", "success", "INTEGER")?;
+            add_column(tx, "events", "duration_ms", "INTEGER")?;
+            add_column(tx, "events", "summary", "TEXT")
+        },
+    },
+    Migration {
+        version: 11,
+        description: "Add full-text search indexes",
+        up: create_search_indexes,
+    },
+    Migration {
+        version: 12,
+        description: "Create event rollups",
+        up: |tx| {
+            // Per-session, per-day counts kept for events removed by retention.
+            // tool_name is '' rather than NULL so it can be part of the key.
+            tx.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS event_rollups (
+                    project_dir TEXT NOT NULL,
+                    session_id TEXT NOT NULL,
+                    day TEXT NOT NULL,
+                    event_type TEXT NOT NULL,
+                    tool_name TEXT NOT NULL DEFAULT '',
+                    source_agent TEXT NOT NULL,
+                    event_count INTEGER NOT NULL DEFAULT 0,
+                    failure_count INTEGER NOT NULL DEFAULT 0,
+                    total_duration_ms INTEGER NOT NULL DEFAULT 0,
+                    first_at TEXT NOT NULL,
+                    last_at TEXT NOT NULL,
+                    PRIMARY KEY (project_dir, session_id, day, event_type, tool_name)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_event_rollups_day ON event_rollups(project_dir, day);
+                "#,
+            )
+        },
+    },
+    Migration {
+        version: 13,
+        description: "Create feature history",
+        up: |tx| {
+            // Audit log of feature field changes; kept after the feature is deleted
+            tx.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS feature_history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    feature_id TEXT NOT NULL,
+                    field TEXT NOT NULL,
+                    old_value TEXT,
+                    new_value TEXT,
+                    source TEXT NOT NULL,
+                    agent TEXT,
+                    session_id TEXT,
+                    changed_at TEXT NOT NULL
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_feature_history_feature ON feature_history(feature_id, id);
+                "#,
+            )
+        },
+    },
+    Migration {
+        version: 14,
+        description: "Create per-field human override locks",
+        up: |tx| {
+            // Locks are enforced per field; features.human_override_until only
+            // keeps the latest timed expiry for the board's lock badge.
+            // A NULL locked_until holds the lock until it is released.
+            tx.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS feature_locks (
+                    feature_id TEXT NOT NULL,
+                    field TEXT NOT NULL,
+                    locked_until TEXT,
+                    locked_at TEXT NOT NULL,
+                    PRIMARY KEY (feature_id, field)
+                );
+                "#,
+            )
+        },
+    },
+    Migration {
+        version: 15,
+        description: "Scope event idempotency keys to the submitting agent",
+        up: |tx| {
+            // Agents pick their own keys, so one agent's key must not swallow
+            // another agent's event
+            tx.execute_batch(
+                r#"
+                DROP INDEX IF EXISTS idx_events_idempotency_key;
+                CREATE UNIQUE INDEX IF NOT EXISTS idx_events_agent_idempotency_key
+                    ON events(source_agent, idempotency_key);
+                "#,
+            )
+        },
+    },
+];
+
+/// Apply all pending migrations
+pub fn run(conn: &mut Connection) -> Result<(), rusqlite::Error> {
+    run_migrations(conn, MIGRATIONS)
+}
+
+/// Apply the migrations newer than the database's `user_version`, in order
+pub fn run_migrations(conn: &mut Connection, migrations: &[Migration]) -> Result<(), rusqlite::Error> {
+    let current = schema_version(conn)?;
+    let latest = migrations.last().map_or(0, |m| m.version);
+
+    if current > latest {
+        tracing::warn!(
+            "Database schema version {} is newer than this build supports ({})",
+            current,
+            latest
+        );
+        return Ok(());
+    }
+
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction()?;
+        let result = (migration.up)(&tx)
+            .and_then(|_| tx.pragma_update(None, "user_version", migration.version))
+            .and_then(|_| tx.commit());
+
+        if let Err(e) = result {
+            tracing::error!(
+                "Database migration {} ({}) failed: {}",
+                migration.version,
+                migration.description,
+                e
+            );
+            return Err(e);
+        }
+
+        tracing::info!(
+            "Applied database migration {}: {}",
+            migration.version,
+            migration.description
+        );
+    }
+
+    Ok(())
+}
+
+/// Current schema version (`PRAGMA user_version`)
+pub fn schema_version(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Add a column unless it already exists (pre-versioning databases)
+fn add_column(tx: &Transaction, table: &str, column: &str, definition: &str) -> Result<(), rusqlite::Error> {
+    let exists: bool = tx.query_row(
+        &format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?1", table),
+        [column],
+        |row| row.get(0),
+    )?;
+
+    if !exists {
+        tx.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn create_base_tables(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            event_type TEXT NOT NULL,
+            source_agent TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            project_dir TEXT NOT NULL,
+            tool_name TEXT,
+            payload TEXT,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS features (
+            id TEXT PRIMARY KEY,
+            project_dir TEXT NOT NULL,
+            description TEXT NOT NULL,
+            category TEXT DEFAULT 'functional',
+            passes INTEGER DEFAULT 0,
+            in_progress INTEGER DEFAULT 0,
+            agent TEXT,
+            updated_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS sessions (
+            session_id TEXT PRIMARY KEY,
+            source_agent TEXT NOT NULL,
+            project_dir TEXT NOT NULL,
+            started_at TEXT DEFAULT (datetime('now')),
+            last_activity TEXT DEFAULT (datetime('now')),
+            status TEXT DEFAULT 'active'
+        );
+
+        CREATE TABLE IF NOT EXISTS config (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_events_session ON events(session_id);
+        CREATE INDEX IF NOT EXISTS idx_events_project ON events(project_dir);
+        CREATE INDEX IF NOT EXISTS idx_events_created ON events(created_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_features_project ON features(project_dir);
+        "#,
+    )
+}
+
+fn create_search_indexes(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    // events_fts reads its text from the events table (external content).
+    // Features are written with INSERT OR REPLACE, which skips delete triggers
+    // and changes the rowid, so features_fts keeps its own copy keyed on the
+    // feature id instead.
+    tx.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS events_fts USING fts5(
+            tool_name, payload, tool_input, summary,
+            content = 'events', content_rowid = 'id'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS events_fts_insert AFTER INSERT ON events BEGIN
+            INSERT INTO events_fts (rowid, tool_name, payload, tool_input, summary)
+            VALUES (new.id, new.tool_name, new.payload, new.tool_input, new.summary);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS events_fts_delete AFTER DELETE ON events BEGIN
+            INSERT INTO events_fts (events_fts, rowid, tool_name, payload, tool_input, summary)
+            VALUES ('delete', old.id, old.tool_name, old.payload, old.tool_input, old.summary);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS events_fts_update
+        AFTER UPDATE OF tool_name, payload, tool_input, summary ON events BEGIN
+            INSERT INTO events_fts (events_fts, rowid, tool_name, payload, tool_input, summary)
+            VALUES ('delete', old.id, old.tool_name, old.payload, old.tool_input, old.summary);
+            INSERT INTO events_fts (rowid, tool_name, payload, tool_input, summary)
+            VALUES (new.id, new.tool_name, new.payload, new.tool_input, new.summary);
+        END;
+
+        INSERT INTO events_fts (events_fts) VALUES ('rebuild');
+
+        CREATE VIRTUAL TABLE IF NOT EXISTS features_fts USING fts5(
+            feature_id UNINDEXED, description, steps
+        );
+
+        CREATE TRIGGER IF NOT EXISTS features_fts_insert AFTER INSERT ON features BEGIN
+            DELETE FROM features_fts WHERE feature_id = new.id;
+            INSERT INTO features_fts (feature_id, description, steps)
+            VALUES (new.id, new.description, new.steps);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS features_fts_delete AFTER DELETE ON features BEGIN
+            DELETE FROM features_fts WHERE feature_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS features_fts_update
+        AFTER UPDATE OF id, description, steps ON features BEGIN
+            DELETE FROM features_fts WHERE feature_id = old.id;
+            INSERT INTO features_fts (feature_id, description, steps)
+            VALUES (new.id, new.description, new.steps);
+        END;
+
+        DELETE FROM features_fts;
+        INSERT INTO features_fts (feature_id, description, steps)
+        SELECT id, description, steps FROM features;
+        "#,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column_names(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<String>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_migration_versions_are_sequential() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as i64 + 1, "{}", migration.description);
+        }
+    }
+
+    #[test]
+    fn test_fresh_database_reaches_latest_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.last().unwrap().version);
+        assert!(column_names(&conn, "features").contains(&"graph_updated_at".to_string()));
+
+        // Re-running is a no-op
+        run(&mut conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.last().unwrap().version);
+    }
+
+    #[test]
+    fn test_unversioned_database_is_upgraded() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        // Shape left behind by the old ignore-errors ALTER TABLE migrations
+        conn.execute_batch(
+            r#"
+            CREATE TABLE features (
+                id TEXT PRIMARY KEY,
+                project_dir TEXT NOT NULL,
+                description TEXT NOT NULL,
+                category TEXT DEFAULT 'functional',
+                passes INTEGER DEFAULT 0,
+                in_progress INTEGER DEFAULT 0,
+                agent TEXT,
+                steps TEXT,
+                updated_at TEXT
+            );
+            INSERT INTO features (id, project_dir, description, updated_at)
+                VALUES ('a', '/p', 'rfc', '2025-01-02T03:04:05.123+02:00'),
+                       ('b', '/p', 'sqlite', '2025-01-02 03:04:05'),
+                       ('c', '/p', 'missing', NULL);
+            "#,
+        )
+        .unwrap();
+
+        run(&mut conn).unwrap();
+
+        let columns = column_names(&conn, "features");
+        assert!(columns.contains(&"human_override_until".to_string()));
+        assert!(columns.contains(&"from_graph".to_string()));
+
+        let updated_at = |id: &str| -> String {
+            conn.query_row("SELECT updated_at FROM features WHERE id = ?1", [id], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(updated_at("a"), "2025-01-02 01:04:05");
+        assert_eq!(updated_at("b"), "2025-01-02 03:04:05");
+        assert!(!updated_at("c").is_empty());
+
+        // Existing features are backfilled into the search index
+        let indexed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM features_fts WHERE features_fts MATCH 'sqlite'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexed, 1);
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let migrations = [
+            Migration {
+                version: 1,
+                description: "ok",
+                up: |tx| tx.execute_batch("CREATE TABLE t (id INTEGER);"),
+            },
+            Migration {
+                version: 2,
+                description: "broken",
+                up: |tx| tx.execute_batch("CREATE TABLE u (id INTEGER); NOT VALID SQL;"),
+            },
+        ];
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert!(run_migrations(&mut conn, &migrations).is_err());
+
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+        let u_exists: bool = conn
+            .query_row("SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'u'", [], |row| row.get(0))
+            .unwrap();
+        assert!(!u_exists);
+    }
+}
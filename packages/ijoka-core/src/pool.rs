@@ -0,0 +1,200 @@
+//! SQLite Connection Pool
+//!
+//! One writer connection behind a mutex plus a fixed set of read-only
+//! connections. In WAL mode readers never wait on the writer, so a slow
+//! query no longer holds up hook ingestion.
+//!
+//! In-memory databases cannot be shared between connections, so they get no
+//! readers and every read goes through the writer.
+
+use rusqlite::{Connection, OpenFlags};
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+pub struct Pool {
+    writer: Mutex<Connection>,
+    readers: Mutex<Vec<Connection>>,
+    reader_available: Condvar,
+    reader_count: usize,
+}
+
+impl Pool {
+    /// Wrap an already configured and migrated writer connection, opening
+    /// `reader_count` read-only connections to the same file
+    pub fn new(writer: Connection, path: &Path, reader_count: usize) -> Result<Self, rusqlite::Error> {
+        let reader_count = if is_in_memory(path) { 0 } else { reader_count };
+
+        let readers = (0..reader_count)
+            .map(|_| open_reader(path))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            writer: Mutex::new(writer),
+            readers: Mutex::new(readers),
+            reader_available: Condvar::new(),
+            reader_count,
+        })
+    }
+
+    /// The writer connection. Held exclusively; use it for anything that
+    /// modifies the database or must read its own writes atomically.
+    pub fn write(&self) -> MutexGuard<'_, Connection> {
+        self.writer.lock().unwrap()
+    }
+
+    /// A read-only connection, waiting for one to be returned if all are busy
+    pub fn read(&self) -> ReadConnection<'_> {
+        if self.reader_count == 0 {
+            return ReadConnection::Writer(self.write());
+        }
+
+        let mut idle = self.readers.lock().unwrap();
+        loop {
+            if let Some(conn) = idle.pop() {
+                return ReadConnection::Pooled {
+                    pool: self,
+                    conn: Some(conn),
+                };
+            }
+            idle = self.reader_available.wait(idle).unwrap();
+        }
+    }
+
+    pub fn reader_count(&self) -> usize {
+        self.reader_count
+    }
+
+    fn release(&self, conn: Connection) {
+        self.readers.lock().unwrap().push(conn);
+        self.reader_available.notify_one();
+    }
+}
+
+/// A connection borrowed for reading, returned to the pool on drop
+pub enum ReadConnection<'a> {
+    Pooled {
+        pool: &'a Pool,
+        conn: Option<Connection>,
+    },
+    Writer(MutexGuard<'a, Connection>),
+}
+
+impl Deref for ReadConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            ReadConnection::Pooled { conn, .. } => conn.as_ref().expect("reader already released"),
+            ReadConnection::Writer(conn) => conn,
+        }
+    }
+}
+
+impl Drop for ReadConnection<'_> {
+    fn drop(&mut self) {
+        if let ReadConnection::Pooled { pool, conn } = self {
+            if let Some(conn) = conn.take() {
+                pool.release(conn);
+            }
+        }
+    }
+}
+
+fn is_in_memory(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    path.is_empty() || path == ":memory:" || path.contains("mode=memory")
+}
+
+fn open_reader(path: &Path) -> Result<Connection, rusqlite::Error> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX | OpenFlags::SQLITE_OPEN_URI,
+    )?;
+    conn.execute_batch(
+        r#"
+        PRAGMA busy_timeout = 10000;
+        PRAGMA cache_size = -2000;
+        "#,
+    )?;
+    Ok(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("ijoka-pool-{}-{}.db", name, std::process::id()));
+        remove_db(&path);
+        path
+    }
+
+    fn remove_db(path: &Path) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    fn open_pool(path: &Path, readers: usize) -> Pool {
+        let writer = Connection::open(path).unwrap();
+        writer
+            .execute_batch("PRAGMA journal_mode = WAL; CREATE TABLE t (v INTEGER);")
+            .unwrap();
+        Pool::new(writer, path, readers).unwrap()
+    }
+
+    #[test]
+    fn test_readers_see_committed_writes() {
+        let path = temp_db_path("visible");
+        let pool = open_pool(&path, 2);
+
+        pool.write().execute("INSERT INTO t (v) VALUES (1)", []).unwrap();
+        let count: i64 = pool
+            .read()
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+
+        // Readers are opened read-only
+        assert!(pool.read().execute("INSERT INTO t (v) VALUES (2)", []).is_err());
+
+        drop(pool);
+        remove_db(&path);
+    }
+
+    #[test]
+    fn test_read_does_not_wait_for_writer() {
+        let path = temp_db_path("concurrent");
+        let pool = open_pool(&path, 1);
+
+        // An open write transaction holds the writer lock
+        let writer = pool.write();
+        writer.execute_batch("BEGIN; INSERT INTO t (v) VALUES (1);").unwrap();
+
+        // ...but a reader still gets the last committed snapshot
+        let count: i64 = pool
+            .read()
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+
+        writer.execute_batch("COMMIT;").unwrap();
+        drop(writer);
+        drop(pool);
+        remove_db(&path);
+    }
+
+    #[test]
+    fn test_in_memory_reads_use_writer() {
+        let pool = open_pool(Path::new(":memory:"), 4);
+        assert_eq!(pool.reader_count(), 0);
+
+        pool.write().execute("INSERT INTO t (v) VALUES (1)", []).unwrap();
+        let count: i64 = pool
+            .read()
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+}
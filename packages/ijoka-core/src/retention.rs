@@ -0,0 +1,269 @@
+//! Event Retention
+//!
+//! Applies the configured `RetentionPolicy`. Expired events are written to a
+//! gzip-compressed NDJSON archive under ~/.ijoka/archive, folded into
+//! per-session/per-day rollups and deleted. Runs periodically in the daemon
+//! and on demand through the `compact_database` command.
+
+use crate::db::{AgentEvent, Database, RetentionPolicy};
+use crate::events::EventSink;
+use anyhow::Result;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Events archived and deleted per transaction
+const BATCH_SIZE: i64 = 5_000;
+
+/// How often the background job applies the policy
+const COMPACTION_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Get the standard archive directory: ~/.ijoka/archive
+pub fn get_archive_dir() -> PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".ijoka")
+        .join("archive")
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionReport {
+    /// Events rolled up and deleted
+    pub compacted: usize,
+    /// Archive written by this run, if any events were archived
+    pub archive_path: Option<String>,
+    pub vacuumed: bool,
+}
+
+/// Apply the configured retention policy, optionally reclaiming disk space
+/// afterwards. Blocking; call it through `Database::run` from async code.
+pub fn compact_database(db: &Database, vacuum: bool) -> Result<CompactionReport> {
+    let policy = db.get_config()?.retention;
+    let mut report = compact(db, &policy, &get_archive_dir(), chrono::Utc::now())?;
+
+    if vacuum {
+        db.vacuum()?;
+        report.vacuumed = true;
+    }
+
+    Ok(report)
+}
+
+/// Archive, roll up and delete every event past `policy` as of `now`
+pub fn compact(
+    db: &Database,
+    policy: &RetentionPolicy,
+    archive_dir: &Path,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<CompactionReport> {
+    let mut report = CompactionReport::default();
+    let mut archive: Option<Archive> = None;
+
+    loop {
+        let events = db.get_expired_events(policy, now, BATCH_SIZE)?;
+        if events.is_empty() {
+            break;
+        }
+
+        // Archive before deleting so a failed write never loses events
+        if policy.archive {
+            let archive = match &mut archive {
+                Some(archive) => archive,
+                None => archive.insert(Archive::create(archive_dir, now)?),
+            };
+            archive.write(&events)?;
+        }
+
+        let ids: Vec<i64> = events.iter().filter_map(|e| e.id).collect();
+        let deleted = db.compact_events(&ids)?;
+        report.compacted += deleted;
+
+        if deleted == 0 || (events.len() as i64) < BATCH_SIZE {
+            break;
+        }
+    }
+
+    if let Some(archive) = archive {
+        report.archive_path = Some(archive.finish()?.to_string_lossy().into_owned());
+    }
+
+    Ok(report)
+}
+
+/// Periodically apply the retention policy, starting shortly after launch
+pub async fn run_retention_job(db: Arc<Database>, sink: Arc<dyn EventSink>) {
+    // Let startup work (graph sync, session cleanup) go first
+    tokio::time::sleep(Duration::from_secs(60)).await;
+
+    let mut interval = tokio::time::interval(COMPACTION_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        match db.run(|db| compact_database(db, false)).await {
+            Ok(report) if report.compacted > 0 => {
+                tracing::info!(
+                    "Retention: compacted {} events (archive: {:?})",
+                    report.compacted,
+                    report.archive_path
+                );
+                sink.emit("database-compacted", json!(report));
+            }
+            Err(e) => {
+                tracing::error!("Retention job failed: {}", e);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// One gzip-compressed NDJSON file per compaction run
+struct Archive {
+    path: PathBuf,
+    writer: GzEncoder<BufWriter<File>>,
+}
+
+impl Archive {
+    fn create(dir: &Path, now: chrono::DateTime<chrono::Utc>) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+
+        let stem = format!("events-{}", now.format("%Y%m%dT%H%M%SZ"));
+        let mut path = dir.join(format!("{}.ndjson.gz", stem));
+        let mut n = 1;
+        while path.exists() {
+            path = dir.join(format!("{}-{}.ndjson.gz", stem, n));
+            n += 1;
+        }
+
+        let file = File::create(&path)?;
+        Ok(Self {
+            path,
+            writer: GzEncoder::new(BufWriter::new(file), Compression::default()),
+        })
+    }
+
+    fn write(&mut self, events: &[AgentEvent]) -> Result<()> {
+        for event in events {
+            serde_json::to_writer(&mut self.writer, event)?;
+            self.writer.write_all(b"\n")?;
+        }
+        // Push the batch to disk before its rows are deleted
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<PathBuf> {
+        self.writer.finish()?.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        Ok(self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{fixtures, EventDetails};
+    use flate2::read::GzDecoder;
+    use std::io::{BufRead, BufReader};
+
+    fn event(project: &str, session: &str, tool: &str, success: Option<bool>) -> AgentEvent {
+        AgentEvent {
+            session_id: session.to_string(),
+            details: EventDetails {
+                success,
+                duration_ms: Some(10),
+                ..Default::default()
+            },
+            ..fixtures::event(tool, project, r#"{"command":"cargo test"}"#)
+        }
+    }
+
+    fn archive_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ijoka-archive-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_row_limit_keeps_newest_per_project() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        for tool in ["Bash", "Bash", "Read", "Edit"] {
+            db.insert_event(&event("/a", "s1", tool, Some(tool != "Read"))).unwrap();
+        }
+        db.insert_event(&event("/b", "s2", "Bash", None)).unwrap();
+
+        let policy = RetentionPolicy {
+            max_age_days: None,
+            max_rows_per_project: Some(1),
+            archive: true,
+        };
+        let dir = archive_dir("rows");
+        let report = compact(&db, &policy, &dir, chrono::Utc::now()).unwrap();
+        assert_eq!(report.compacted, 3);
+
+        // Only the newest /a event is left; /b is within its limit
+        let mut remaining: Vec<_> = db.get_events(10).unwrap().into_iter().map(|e| e.project_dir).collect();
+        remaining.sort();
+        assert_eq!(remaining, ["/a", "/b"]);
+
+        let rollups = db.get_event_rollups("/a", 10).unwrap();
+        let bash = rollups.iter().find(|r| r.tool_name.as_deref() == Some("Bash")).unwrap();
+        assert_eq!((bash.event_count, bash.failure_count, bash.total_duration_ms), (2, 0, 20));
+        let read = rollups.iter().find(|r| r.tool_name.as_deref() == Some("Read")).unwrap();
+        assert_eq!((read.event_count, read.failure_count), (1, 1));
+
+        // The archive holds the raw events as NDJSON
+        let file = File::open(report.archive_path.unwrap()).unwrap();
+        let lines: Vec<AgentEvent> = BufReader::new(GzDecoder::new(file))
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|e| e.project_dir == "/a"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_max_age_without_archive() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        db.insert_event(&event("/a", "s1", "Bash", Some(true))).unwrap();
+
+        let policy = RetentionPolicy {
+            max_age_days: Some(1),
+            max_rows_per_project: None,
+            archive: false,
+        };
+        let dir = archive_dir("age");
+
+        // Nothing is a day old yet
+        let report = compact(&db, &policy, &dir, chrono::Utc::now()).unwrap();
+        assert_eq!(report.compacted, 0);
+
+        let two_days_later = chrono::Utc::now() + chrono::Duration::days(2);
+        let report = compact(&db, &policy, &dir, two_days_later).unwrap();
+        assert_eq!(report.compacted, 1);
+        assert!(report.archive_path.is_none());
+        assert!(!dir.exists());
+        assert!(db.get_events(10).unwrap().is_empty());
+        assert_eq!(db.get_event_rollups("/a", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_default_policy_keeps_everything() {
+        let db = Database::new(Path::new(":memory:")).unwrap();
+        db.insert_event(&event("/a", "s1", "Bash", Some(true))).unwrap();
+
+        let dir = archive_dir("default");
+        let years_later = chrono::Utc::now() + chrono::Duration::days(3650);
+        let report = compact(&db, &RetentionPolicy::default(), &dir, years_later).unwrap();
+        assert_eq!(report.compacted, 0);
+        assert!(!dir.exists());
+        assert_eq!(db.get_events(10).unwrap().len(), 1);
+    }
+}
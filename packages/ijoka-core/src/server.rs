@@ -0,0 +1,1968 @@
+use crate::auth::TokenStore;
+use crate::db::{
+    AgentEvent, Database, EventDetails, EventFilter, Feature, FeatureChange, FeatureUpdate, InsertedEvent,
+    SearchQuery, SearchResults, Session, UpdateAuthor, UpdateOutcome, UpdateSource,
+};
+use crate::events::EventSink;
+use crate::hook_event::HookEvent;
+use crate::mcp::McpServer;
+use crate::store::{DataStore, StaleFeature};
+use crate::graph_db::{self, GraphDb};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        rejection::JsonRejection,
+        DefaultBodyLimit, FromRequest, Path, Query, Request, State,
+    },
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event as SseEvent, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, patch, post},
+    Extension, Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use futures_util::Stream;
+use std::collections::{HashSet, VecDeque};
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tower_http::cors::CorsLayer;
+
+#[derive(Clone)]
+struct AppState {
+    db: Arc<Database>,
+    graph: Arc<GraphDb>,
+    sink: Arc<dyn EventSink>,
+    event_tx: Arc<broadcast::Sender<AgentEvent>>,
+    /// Project paths already upserted into the graph during this run
+    graph_projects: Arc<Mutex<HashSet<String>>>,
+    tokens: Arc<TokenStore>,
+}
+
+/// Agent identity resolved from the request's bearer token
+#[derive(Clone)]
+struct AuthenticatedAgent(String);
+
+/// Origins the Tauri webview loads from (custom protocol on macOS/Linux,
+/// http(s)://tauri.localhost on Windows)
+const TAURI_ORIGINS: &[&str] = &["tauri://localhost", "http://tauri.localhost", "https://tauri.localhost"];
+
+/// Vite dev server origin (tauri.conf.json devUrl)
+const DEV_ORIGIN: &str = "http://localhost:4001";
+
+/// Request body limit for batch ingestion (axum's default is 2 MB)
+const BATCH_BODY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Events read from SQLite per replay when a stream subscriber resumes or lags
+const STREAM_REPLAY_BATCH: i64 = 500;
+
+/// Routes that accept the token as a `?token=` query parameter, since
+/// EventSource and browser WebSockets cannot set headers
+const QUERY_TOKEN_PATHS: &[&str] = &["/events/stream", "/events/ws"];
+
+/// Backoff between attempts to bind a busy port, doubling up to the maximum
+const BIND_RETRY_INITIAL: Duration = Duration::from_secs(1);
+const BIND_RETRY_MAX: Duration = Duration::from_secs(30);
+
+/// Get the standard socket path: ~/.ijoka/ijoka.sock
+pub fn get_socket_path() -> PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".ijoka")
+        .join("ijoka.sock")
+}
+
+/// Host the TCP listener binds to. Defaults to loopback; set `IJOKA_BIND_ADDR`
+/// (e.g. `0.0.0.0`) to expose a headless daemon running in a container.
+pub fn get_bind_host() -> String {
+    std::env::var("IJOKA_BIND_ADDR")
+        .ok()
+        .filter(|host| !host.trim().is_empty())
+        .unwrap_or_else(|| "127.0.0.1".to_string())
+}
+
+/// Listener state, reported to the frontend via `server-status` events
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    pub port: u16,
+    /// Whether the TCP listener is bound
+    pub listening: bool,
+    /// Why the TCP listener could not be bound
+    pub error: Option<String>,
+    /// Unix socket path, once listening
+    pub socket_path: Option<String>,
+    pub socket_error: Option<String>,
+}
+
+/// Runtime control of the ingestion server's listeners
+pub struct ServerControl {
+    port: watch::Sender<u16>,
+    status: Mutex<ServerStatus>,
+}
+
+impl ServerControl {
+    pub fn new(port: u16) -> Self {
+        Self {
+            port: watch::Sender::new(port),
+            status: Mutex::new(ServerStatus {
+                port,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Move the TCP listener to another port. No-op if it is unchanged.
+    pub fn set_port(&self, port: u16) {
+        self.port.send_if_modified(|current| {
+            let changed = *current != port;
+            *current = port;
+            changed
+        });
+    }
+
+    /// The port the TCP listener is (or will be) bound to
+    pub fn port(&self) -> u16 {
+        *self.port.borrow()
+    }
+
+    pub fn status(&self) -> ServerStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    fn update_status(&self, sink: &dyn EventSink, update: impl FnOnce(&mut ServerStatus)) {
+        let status = {
+            let mut status = self.status.lock().unwrap();
+            update(&mut status);
+            status.clone()
+        };
+        sink.emit("server-status", json!(status));
+    }
+}
+
+/// State wrapper for the server control handle
+pub struct ServerState(pub Arc<ServerControl>);
+
+/// Serve the ingestion API on the bind host at the configured port and on the
+/// Unix socket. The TCP listener is rebound whenever the port changes; bind
+/// failures are reported through the server status and retried rather than
+/// ending the task.
+pub async fn start_server(
+    db: Arc<Database>,
+    graph: Arc<GraphDb>,
+    sink: Arc<dyn EventSink>,
+    event_tx: Arc<broadcast::Sender<AgentEvent>>,
+    control: Arc<ServerControl>,
+) {
+    let state = AppState {
+        db,
+        graph,
+        sink: Arc::clone(&sink),
+        event_tx,
+        graph_projects: Arc::new(Mutex::new(HashSet::new())),
+        tokens: Arc::new(TokenStore::open_default()),
+    };
+    let router = build_router(state);
+
+    #[cfg(unix)]
+    {
+        let socket_router = router.clone();
+        let socket_control = Arc::clone(&control);
+        let socket_sink = Arc::clone(&sink);
+        tokio::spawn(async move {
+            let path = get_socket_path();
+            if let Err(e) = serve_unix_socket(socket_router, &path, &socket_control, socket_sink.as_ref()).await {
+                tracing::error!("Unix socket listener on {:?} failed: {}", path, e);
+                socket_control.update_status(socket_sink.as_ref(), |status| {
+                    status.socket_path = None;
+                    status.socket_error = Some(e.to_string());
+                });
+            }
+        });
+    }
+
+    serve_tcp(router, &get_bind_host(), &control, sink.as_ref()).await;
+}
+
+/// Serve the router on `host` at the control's port until the port sender is
+/// dropped. A port that cannot be bound is retried with backoff (e.g. while a
+/// previous instance shuts down) until it binds or the port changes.
+async fn serve_tcp(router: Router, host: &str, control: &ServerControl, sink: &dyn EventSink) {
+    let mut port_rx = control.port.subscribe();
+    let mut retry_delay = BIND_RETRY_INITIAL;
+    loop {
+        let port = *port_rx.borrow_and_update();
+
+        match tokio::net::TcpListener::bind((host, port)).await {
+            Ok(listener) => {
+                tracing::info!("HTTP server listening on http://{}:{}", host, port);
+                retry_delay = BIND_RETRY_INITIAL;
+                control.update_status(sink, |status| {
+                    status.port = port;
+                    status.listening = true;
+                    status.error = None;
+                });
+
+                // The listener closes as soon as the port changes; open
+                // connections (e.g. event streams) are left to finish
+                let mut shutdown_rx = port_rx.clone();
+                let server = axum::serve(listener, router.clone()).with_graceful_shutdown(async move {
+                    let _ = shutdown_rx.changed().await;
+                });
+                tokio::spawn(async move {
+                    if let Err(e) = server.await {
+                        tracing::error!("HTTP server error: {}", e);
+                    }
+                });
+            }
+            Err(e) => {
+                tracing::error!("HTTP server could not bind port {}: {}", port, e);
+                control.update_status(sink, |status| {
+                    status.port = port;
+                    status.listening = false;
+                    status.error = Some(format!("Could not listen on port {}: {}", port, e));
+                });
+
+                tokio::select! {
+                    changed = port_rx.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        retry_delay = BIND_RETRY_INITIAL;
+                    }
+                    _ = tokio::time::sleep(retry_delay) => {
+                        retry_delay = (retry_delay * 2).min(BIND_RETRY_MAX);
+                    }
+                }
+                continue;
+            }
+        }
+
+        if port_rx.changed().await.is_err() {
+            break;
+        }
+    }
+}
+
+fn build_router(state: AppState) -> Router {
+    let mut origins = TAURI_ORIGINS.to_vec();
+    if cfg!(debug_assertions) {
+        origins.push(DEV_ORIGIN);
+    }
+    let cors = CorsLayer::new()
+        .allow_origin(
+            origins
+                .into_iter()
+                .map(HeaderValue::from_static)
+                .collect::<Vec<_>>(),
+        )
+        .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE]);
+
+    Router::new()
+        .route("/health", get(health))
+        .route("/events", get(get_events).post(receive_event))
+        .route(
+            "/events/batch",
+            post(receive_event_batch).layer(DefaultBodyLimit::max(BATCH_BODY_LIMIT)),
+        )
+        .route("/events/stream", get(stream_events_sse))
+        .route("/events/ws", get(stream_events_ws))
+        .route("/events/feature-update", post(receive_feature_update))
+        .route("/features", get(get_features))
+        .route(
+            "/projects/:project/features",
+            get(get_project_features).post(create_feature),
+        )
+        .route(
+            "/projects/:project/features/:id",
+            patch(update_feature).delete(delete_feature),
+        )
+        .route("/projects/:project/features/:id/history", get(get_feature_history))
+        .route("/rules", get(get_rules).post(create_rule))
+        .route("/rules/:id", patch(update_rule))
+        .route("/insights", get(get_insights).post(create_insight))
+        .route("/events/:id/link", post(link_event))
+        .route("/mcp", post(mcp_endpoint))
+        .route("/search", get(search))
+        .route("/sessions", get(get_sessions))
+        .route("/sessions/start", post(session_start))
+        .route("/sessions/end", post(session_end))
+        .fallback(not_found)
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .layer(cors)
+        .with_state(state)
+}
+
+/// Serve the router on a Unix domain socket, so local hooks don't depend on
+/// the TCP port being free. The socket is owner-only; requests still need a
+/// bearer token to identify the agent.
+#[cfg(unix)]
+async fn serve_unix_socket(
+    router: Router,
+    path: &std::path::Path,
+    control: &ServerControl,
+    sink: &dyn EventSink,
+) -> std::io::Result<()> {
+    use hyper::body::Incoming;
+    use hyper_util::rt::TokioIo;
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::{UnixListener, UnixStream};
+    use tower::ServiceExt;
+
+    if path.exists() {
+        // Don't steal the socket from another running instance
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                "another Ijoka instance is listening on the socket",
+            ));
+        }
+        std::fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+    tracing::info!("HTTP server listening on unix:{}", path.display());
+    control.update_status(sink, |status| {
+        status.socket_path = Some(path.display().to_string());
+        status.socket_error = None;
+    });
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                tracing::warn!("Failed to accept socket connection: {}", e);
+                continue;
+            }
+        };
+
+        let router = router.clone();
+        tokio::spawn(async move {
+            let service = hyper::service::service_fn(move |request: hyper::Request<Incoming>| {
+                router.clone().oneshot(request)
+            });
+            if let Err(e) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .with_upgrades()
+                .await
+            {
+                tracing::debug!("Socket connection error: {}", e);
+            }
+        });
+    }
+}
+
+async fn health() -> &'static str {
+    "OK"
+}
+
+/// Reject requests without a valid agent bearer token and attach the agent
+/// identity for handlers. `/health` stays open for liveness probes.
+async fn require_token(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    if request.uri().path() == "/health" || request.method() == Method::OPTIONS {
+        return next.run(request).await;
+    }
+
+    let header_token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+
+    let query_token = || {
+        if !QUERY_TOKEN_PATHS.contains(&request.uri().path()) {
+            return None;
+        }
+        request.uri().query().and_then(|query| {
+            query.split('&').find_map(|pair| {
+                let token = pair.strip_prefix("token=")?;
+                urlencoding::decode(token).ok().map(|t| t.into_owned())
+            })
+        })
+    };
+
+    let agent = header_token
+        .or_else(query_token)
+        .and_then(|token| state.tokens.agent_for(&token));
+
+    match agent {
+        Some(agent) => {
+            request.extensions_mut().insert(AuthenticatedAgent(agent));
+            next.run(request).await
+        }
+        None => ApiError::Unauthorized("Missing or invalid bearer token".to_string()).into_response(),
+    }
+}
+
+/// Use the authenticated identity as the event source, noting any mismatch
+fn tag_agent(agent: &AuthenticatedAgent, claimed: &str) -> String {
+    if !claimed.is_empty() && claimed != agent.0 {
+        tracing::debug!("Event claimed source {:?}, tagging as {:?}", claimed, agent.0);
+    }
+    agent.0.clone()
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    limit: Option<i64>,
+    unlinked: Option<bool>,
+    project_dir: Option<String>,
+}
+
+async fn get_events(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Json<Vec<AgentEvent>>, ApiError> {
+    let limit = query.limit.unwrap_or(50);
+
+    let events = state
+        .db
+        .run(move |db| {
+            if query.unlinked.unwrap_or(false) {
+                db.get_unlinked_events(query.project_dir.as_deref(), limit)
+            } else {
+                db.get_events(limit)
+            }
+        })
+        .await?;
+
+    Ok(Json(events))
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: String,
+    project_dir: Option<String>,
+    /// RFC 3339 timestamp or YYYY-MM-DD
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<i64>,
+}
+
+/// Full-text search over event payloads and features, each ranked best
+/// match first in its own list
+async fn search(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<SearchResults>, ApiError> {
+    let query = SearchQuery::parse(
+        &params.q,
+        params.project_dir,
+        params.from.as_deref(),
+        params.to.as_deref(),
+        params.limit,
+    )
+    .map_err(ApiError::Validation)?;
+
+    let hits = state.db.run(move |db| db.search(&query)).await?;
+    Ok(Json(hits))
+}
+
+#[derive(Deserialize)]
+struct FeaturesQuery {
+    project_dir: Option<String>,
+}
+
+async fn get_features(
+    State(state): State<AppState>,
+    Query(query): Query<FeaturesQuery>,
+) -> Result<Json<Vec<Feature>>, ApiError> {
+    let features = state
+        .db
+        .run(move |db| db.get_features(query.project_dir.as_deref()))
+        .await?;
+    Ok(Json(features))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LinkEventRequest {
+    feature_id: String,
+}
+
+async fn link_event(
+    State(state): State<AppState>,
+    Path(event_id): Path<i64>,
+    ApiJson(request): ApiJson<LinkEventRequest>,
+) -> Result<Json<ApiResponse>, ApiError> {
+    let linked = state
+        .db
+        .run(move |db| db.link_event_to_feature(event_id, &request.feature_id))
+        .await?;
+
+    if !linked {
+        return Err(ApiError::NotFound(format!("Event {} not found", event_id)));
+    }
+
+    Ok(Json(ApiResponse { ok: true }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IncomingEvent {
+    event_type: String,
+    source_agent: String,
+    session_id: String,
+    project_dir: String,
+    tool_name: Option<String>,
+    payload: Option<serde_json::Value>,
+    feature_id: Option<String>,
+    /// Retries with the same key are acknowledged without being stored twice
+    idempotency_key: Option<String>,
+}
+
+impl IncomingEvent {
+    /// Validate against the typed hook model and build the event to store
+    fn into_event(self, agent: &AuthenticatedAgent) -> Result<AgentEvent, String> {
+        let hook = HookEvent::parse(&self.event_type, self.tool_name.as_deref(), self.payload.as_ref())?;
+
+        Ok(AgentEvent {
+            id: None,
+            event_type: hook.event_type().to_string(),
+            source_agent: tag_agent(agent, &self.source_agent),
+            session_id: self.session_id,
+            project_dir: self.project_dir,
+            tool_name: hook.tool_name().map(String::from).or(self.tool_name),
+            payload: self.payload.map(|p| p.to_string()),
+            feature_id: self.feature_id,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            idempotency_key: self.idempotency_key.filter(|k| !k.is_empty()),
+            details: hook.details(),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct ApiResponse {
+    ok: bool,
+}
+
+/// Acknowledgement for a stored (or already stored) event
+#[derive(Serialize)]
+struct EventAck {
+    ok: bool,
+    id: i64,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    duplicate: bool,
+}
+
+// =============================================================================
+// ERRORS
+// =============================================================================
+
+/// Handler failure, rendered as `{ ok: false, code, error }` with a matching
+/// HTTP status so clients can tell "empty" from "broken"
+#[derive(Debug)]
+enum ApiError {
+    /// Malformed request body (not JSON, wrong shape)
+    BadRequest(String),
+    Unauthorized(String),
+    NotFound(String),
+    /// The write collides with existing data
+    Conflict(String),
+    /// An agent update sets fields a human has locked
+    Locked { feature_id: String, fields: Vec<String> },
+    /// Well-formed but fails validation (e.g. an invalid hook payload)
+    Validation(String),
+    Database(rusqlite::Error),
+    Graph(anyhow::Error),
+}
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) | ApiError::Locked { .. } => StatusCode::CONFLICT,
+            ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Graph(e) if graph_db::is_unavailable_error(e) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Graph(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Machine-readable error code
+    fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Locked { .. } => "fields_locked",
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::Database(_) => "database_error",
+            ApiError::Graph(e) if graph_db::is_unavailable_error(e) => "graph_unavailable",
+            ApiError::Graph(_) => "graph_error",
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::BadRequest(msg)
+            | ApiError::Unauthorized(msg)
+            | ApiError::NotFound(msg)
+            | ApiError::Conflict(msg)
+            | ApiError::Validation(msg) => write!(f, "{}", msg),
+            ApiError::Locked { feature_id, fields } => write!(
+                f,
+                "Feature {} has fields locked by a human edit: {}",
+                feature_id,
+                fields.join(", ")
+            ),
+            ApiError::Database(e) => write!(f, "Database error: {}", e),
+            ApiError::Graph(e) => write!(f, "Graph database error: {}", e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(e: rusqlite::Error) -> Self {
+        if e.sqlite_error_code() == Some(rusqlite::ErrorCode::ConstraintViolation) {
+            ApiError::Conflict(e.to_string())
+        } else {
+            ApiError::Database(e)
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        if e.is::<StaleFeature>() {
+            return ApiError::Conflict(e.to_string());
+        }
+        if let Some(graph_db::StatusLocked(feature_id)) = e.downcast_ref() {
+            return ApiError::Locked {
+                feature_id: feature_id.clone(),
+                fields: vec!["status".to_string()],
+            };
+        }
+        match e.downcast::<rusqlite::Error>() {
+            Ok(e) => e.into(),
+            Err(e) => ApiError::Graph(e),
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            // Valid JSON that doesn't match the expected fields
+            JsonRejection::JsonDataError(e) => ApiError::Validation(e.body_text()),
+            other => ApiError::BadRequest(other.body_text()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ApiErrorBody {
+    ok: bool,
+    code: &'static str,
+    error: String,
+    /// Fields that refused the write, for `fields_locked`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<Vec<String>>,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        if status.is_server_error() {
+            tracing::error!("Request failed: {}", self);
+        }
+
+        let body = ApiErrorBody {
+            ok: false,
+            code: self.code(),
+            error: self.to_string(),
+            fields: match &self {
+                ApiError::Locked { fields, .. } => Some(fields.clone()),
+                _ => None,
+            },
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+/// `Json` extractor whose rejections are reported as `ApiError`s
+struct ApiJson<T>(T);
+
+#[axum::async_trait]
+impl<S, T> FromRequest<S> for ApiJson<T>
+where
+    Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state).await?;
+        Ok(ApiJson(value))
+    }
+}
+
+async fn not_found() -> ApiError {
+    ApiError::NotFound("No such route".to_string())
+}
+
+async fn receive_event(
+    State(state): State<AppState>,
+    Extension(agent): Extension<AuthenticatedAgent>,
+    ApiJson(incoming): ApiJson<IncomingEvent>,
+) -> Result<Json<EventAck>, ApiError> {
+    let mut event = incoming.into_event(&agent).map_err(ApiError::Validation)?;
+
+    // Store in database
+    let row = event.clone();
+    let id = match state.db.run(move |db| db.insert_event(&row)).await? {
+        InsertedEvent::New(id) => id,
+        InsertedEvent::Duplicate(id) => {
+            // A retry of an event we already have; don't store or broadcast it again
+            return Ok(Json(EventAck { ok: true, id, duplicate: true }));
+        }
+    };
+    event.id = Some(id);
+
+    // Mirror into the graph so graph-backed views see hook traffic
+    if let Err(e) = write_event_to_graph(&state, &event).await {
+        tracing::warn!("Failed to write event to graph: {}", e);
+    }
+
+    // Broadcast to frontend
+    let _ = state.event_tx.send(event);
+
+    Ok(Json(EventAck { ok: true, id, duplicate: false }))
+}
+
+// =============================================================================
+// BATCH INGESTION
+// =============================================================================
+
+/// Outcome of one item in a batch, by position in the request
+#[derive(Serialize)]
+struct BatchItemResult {
+    index: usize,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<i64>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    duplicate: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct BatchResponse {
+    ok: bool,
+    accepted: usize,
+    rejected: usize,
+    results: Vec<BatchItemResult>,
+}
+
+/// Parse a batch body as a JSON array or as NDJSON (one event per non-empty
+/// line). Items that fail to parse are reported individually.
+fn parse_event_batch(
+    content_type: &str,
+    body: &str,
+) -> Result<Vec<Result<IncomingEvent, String>>, String> {
+    let is_ndjson = content_type.contains("ndjson")
+        || content_type.contains("jsonl")
+        || !body.trim_start().starts_with('[');
+
+    if is_ndjson {
+        return Ok(body
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(|e| e.to_string()))
+            .collect());
+    }
+
+    let items: Vec<serde_json::Value> =
+        serde_json::from_str(body).map_err(|e| format!("Invalid JSON array: {}", e))?;
+    Ok(items
+        .into_iter()
+        .map(|item| serde_json::from_value(item).map_err(|e| e.to_string()))
+        .collect())
+}
+
+async fn receive_event_batch(
+    State(state): State<AppState>,
+    Extension(agent): Extension<AuthenticatedAgent>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<BatchResponse>, ApiError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    let parsed = parse_event_batch(content_type, &body).map_err(ApiError::BadRequest)?;
+
+    let mut results = Vec::with_capacity(parsed.len());
+    let mut events = Vec::new();
+    let mut positions = Vec::new();
+
+    for (index, item) in parsed.into_iter().enumerate() {
+        match item.and_then(|incoming| incoming.into_event(&agent)) {
+            Ok(event) => {
+                positions.push(results.len());
+                events.push(event);
+                results.push(BatchItemResult {
+                    index,
+                    ok: true,
+                    id: None,
+                    duplicate: false,
+                    error: None,
+                });
+            }
+            Err(e) => results.push(BatchItemResult {
+                index,
+                ok: false,
+                id: None,
+                duplicate: false,
+                error: Some(e),
+            }),
+        }
+    }
+
+    // One transaction for the whole batch; if it fails nothing was stored
+    let rows = events.clone();
+    let inserted = state.db.run(move |db| db.insert_events(&rows)).await?;
+    for ((event, outcome), position) in events.iter_mut().zip(&inserted).zip(&positions) {
+        event.id = Some(outcome.id());
+        results[*position].id = Some(outcome.id());
+        results[*position].duplicate = outcome.is_duplicate();
+    }
+
+    // Already-stored retries are acknowledged but not mirrored or broadcast
+    let mut outcomes = inserted.iter();
+    events.retain(|_| outcomes.next().is_some_and(|o| !o.is_duplicate()));
+
+    // Broadcast in request order now; the graph mirror (several round trips
+    // per event) runs in the background so it doesn't hold up the response
+    for event in &events {
+        let _ = state.event_tx.send(event.clone());
+    }
+    if !events.is_empty() {
+        tokio::spawn(async move {
+            for event in events {
+                if let Err(e) = write_event_to_graph(&state, &event).await {
+                    tracing::warn!("Failed to write event to graph: {}", e);
+                }
+            }
+        });
+    }
+
+    let accepted = results.iter().filter(|r| r.ok).count();
+    Ok(Json(BatchResponse {
+        ok: accepted == results.len(),
+        accepted,
+        rejected: results.len() - accepted,
+        results,
+    }))
+}
+
+// =============================================================================
+// LIVE EVENT STREAM
+// =============================================================================
+
+#[derive(Deserialize)]
+struct StreamQuery {
+    project_dir: Option<String>,
+    session_id: Option<String>,
+    event_type: Option<String>,
+    /// Replay stored events after this id before going live
+    last_event_id: Option<i64>,
+}
+
+impl StreamQuery {
+    fn filter(&self) -> EventFilter {
+        EventFilter {
+            project_dir: self.project_dir.clone(),
+            session_id: self.session_id.clone(),
+            event_type: self.event_type.clone(),
+        }
+    }
+}
+
+/// A filtered subscription to the event broadcast that first replays missed
+/// events from SQLite, and replays again if the subscriber lags behind
+struct EventTail {
+    db: Arc<Database>,
+    rx: broadcast::Receiver<AgentEvent>,
+    filter: EventFilter,
+    backlog: VecDeque<AgentEvent>,
+    /// Stored events may follow the backlog (a full batch was read, or the
+    /// subscriber lagged), so replay again once it drains
+    replay_pending: bool,
+    /// Highest id read from SQLite; live events up to it were already replayed
+    replayed_through: i64,
+    /// Highest id delivered to the subscriber
+    last_delivered: i64,
+}
+
+impl EventTail {
+    async fn new(state: &AppState, filter: EventFilter, last_event_id: Option<i64>) -> Self {
+        // Subscribe before replaying so nothing falls between the two
+        let mut tail = Self {
+            db: Arc::clone(&state.db),
+            rx: state.event_tx.subscribe(),
+            filter,
+            backlog: VecDeque::new(),
+            replay_pending: false,
+            replayed_through: 0,
+            last_delivered: 0,
+        };
+
+        if let Some(id) = last_event_id {
+            tail.last_delivered = id;
+            tail.replay().await;
+        }
+
+        tail
+    }
+
+    async fn replay(&mut self) {
+        let (after, filter) = (self.last_delivered, self.filter.clone());
+        let events = self
+            .db
+            .run(move |db| db.get_events_after(after, &filter, STREAM_REPLAY_BATCH))
+            .await;
+
+        match events {
+            Ok(events) => {
+                self.replay_pending = events.len() as i64 == STREAM_REPLAY_BATCH;
+                if let Some(id) = events.last().and_then(|e| e.id) {
+                    self.replayed_through = self.replayed_through.max(id);
+                }
+                self.backlog.extend(events);
+            }
+            Err(e) => {
+                tracing::error!("Failed to replay events for stream: {}", e);
+                self.replay_pending = false;
+            }
+        }
+    }
+
+    /// Cancel-safe: an event only leaves the tail after the last await, so a
+    /// call dropped by `select!` loses nothing
+    async fn next(&mut self) -> Option<AgentEvent> {
+        loop {
+            if self.backlog.is_empty() && self.replay_pending {
+                self.replay().await;
+            }
+
+            if let Some(event) = self.backlog.pop_front() {
+                self.last_delivered = self.last_delivered.max(event.id.unwrap_or_default());
+                return Some(event);
+            }
+
+            match self.rx.recv().await {
+                Ok(event) => {
+                    let replayed = event.id.is_some_and(|id| id <= self.replayed_through);
+                    if replayed || !self.filter.matches(&event) {
+                        continue;
+                    }
+                    self.last_delivered = self.last_delivered.max(event.id.unwrap_or_default());
+                    return Some(event);
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Event stream subscriber lagged by {} events", skipped);
+                    self.replay_pending = self.last_delivered > 0;
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Server-Sent Events stream of agent events. Honours the standard
+/// `Last-Event-ID` header sent by reconnecting EventSource clients.
+async fn stream_events_sse(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQuery>,
+) -> Sse<impl Stream<Item = Result<SseEvent, Infallible>>> {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .or(query.last_event_id);
+
+    let tail = EventTail::new(&state, query.filter(), last_event_id).await;
+    let stream = futures_util::stream::unfold(tail, |mut tail| async move {
+        let event = tail.next().await?;
+        let mut sse = SseEvent::default()
+            .json_data(&event)
+            .unwrap_or_else(|_| SseEvent::default().comment("unserializable event"));
+        if let Some(id) = event.id {
+            sse = sse.id(id.to_string());
+        }
+        Some((Ok(sse), tail))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// WebSocket stream of agent events, one JSON text message per event
+async fn stream_events_ws(
+    State(state): State<AppState>,
+    Query(query): Query<StreamQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let tail = EventTail::new(&state, query.filter(), query.last_event_id).await;
+    ws.on_upgrade(move |socket| forward_events_ws(socket, tail))
+}
+
+async fn forward_events_ws(mut socket: WebSocket, mut tail: EventTail) {
+    loop {
+        tokio::select! {
+            event = tail.next() => {
+                let Some(event) = event else { break };
+                let Ok(text) = serde_json::to_string(&event) else { continue };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+}
+
+// =============================================================================
+// GRAPH WRITE-THROUGH
+// =============================================================================
+
+/// Create the project node the first time a project is seen during this run
+async fn ensure_graph_project(state: &AppState, project_dir: &str) -> anyhow::Result<()> {
+    if project_dir.is_empty() || state.graph_projects.lock().unwrap().contains(project_dir) {
+        return Ok(());
+    }
+
+    // Create-only, so an existing project's name and settings are kept
+    state
+        .graph
+        .ensure_project(&graph_db::Project::from_path(project_dir))
+        .await?;
+
+    state
+        .graph_projects
+        .lock()
+        .unwrap()
+        .insert(project_dir.to_string());
+    Ok(())
+}
+
+/// Record an event in the graph, creating its project and session if needed
+async fn write_event_to_graph(state: &AppState, event: &AgentEvent) -> anyhow::Result<()> {
+    let graph = &state.graph;
+
+    ensure_graph_project(state, &event.project_dir).await?;
+
+    // start_session merges on the session id, so this only creates missing sessions
+    graph
+        .start_session(&event.session_id, &event.source_agent, &event.project_dir)
+        .await?;
+
+    // The agent-scoped idempotency key doubles as the graph id so retries
+    // MERGE onto one node
+    let graph_event = graph_db::Event {
+        id: event
+            .idempotency_key
+            .as_ref()
+            .map(|key| format!("{}:{}", event.source_agent, key)),
+        event_type: event.event_type.clone(),
+        tool_name: event.tool_name.clone(),
+        payload: event
+            .payload
+            .as_ref()
+            .map(|p| serde_json::from_str(p).unwrap_or(serde_json::Value::String(p.clone()))),
+        summary: event.details.summary.clone(),
+        timestamp: None,
+        success: event.details.success,
+        tool_input: event
+            .details
+            .tool_input
+            .as_ref()
+            .and_then(|input| serde_json::from_str(input).ok()),
+        exit_code: event.details.exit_code,
+        duration_ms: event.details.duration_ms,
+        source_agent: Some(event.source_agent.clone()),
+        session_id: Some(event.session_id.clone()),
+        project_path: Some(event.project_dir.clone()),
+        feature_id: event.feature_id.clone(),
+        feature_description: None,
+    };
+
+    let event_id = graph.record_event(&graph_event, &event.session_id).await?;
+    graph.update_session_activity(&event.session_id).await?;
+
+    if let Some(feature_id) = event.feature_id.as_deref().filter(|f| !f.is_empty()) {
+        graph.link_event_to_feature(&event_id, feature_id).await?;
+    }
+
+    Ok(())
+}
+
+// =============================================================================
+// REST RESOURCES
+// =============================================================================
+//
+// Writes from the HTTP API come from authenticated agents, so they use
+// `UpdateSource::Agent` and never override a human's recent edit.
+
+const FEATURE_STATUSES: &[&str] = &["pending", "in_progress", "complete"];
+
+#[derive(Deserialize)]
+struct LimitQuery {
+    limit: Option<i64>,
+}
+
+async fn get_project_features(
+    State(state): State<AppState>,
+    Path(project_dir): Path<String>,
+) -> Result<Json<Vec<graph_db::Feature>>, ApiError> {
+    let (db, graph) = (&state.db, &state.graph);
+    let features = DataStore::new(graph, db).get_features(&project_dir).await?;
+    Ok(Json(features))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NewFeature {
+    /// Defaults to a generated UUID
+    id: Option<String>,
+    description: String,
+    category: Option<String>,
+    status: Option<String>,
+    priority: Option<i32>,
+    steps: Option<Vec<String>>,
+}
+
+async fn create_feature(
+    State(state): State<AppState>,
+    Path(project_dir): Path<String>,
+    ApiJson(new): ApiJson<NewFeature>,
+) -> Result<(StatusCode, Json<graph_db::Feature>), ApiError> {
+    if new.description.trim().is_empty() {
+        return Err(ApiError::Validation("description must not be empty".to_string()));
+    }
+    let status = new.status.unwrap_or_else(|| "pending".to_string());
+    if !FEATURE_STATUSES.contains(&status.as_str()) {
+        return Err(ApiError::Validation(format!("Unknown feature status {:?}", status)));
+    }
+
+    let (db, graph) = (&state.db, &state.graph);
+    if let Some(id) = new.id.clone() {
+        if let Some(existing) = db.run(move |db| db.get_feature(&id)).await? {
+            return Err(ApiError::Conflict(format!("Feature {} already exists", existing.id)));
+        }
+    }
+
+    ensure_graph_project(&state, &project_dir).await?;
+
+    let feature = graph_db::Feature {
+        id: new.id.filter(|id| !id.is_empty()),
+        description: new.description,
+        category: new.category.unwrap_or_else(|| "functional".to_string()),
+        passes: status == "complete",
+        in_progress: status == "in_progress",
+        status,
+        priority: new.priority,
+        steps: new.steps,
+        created_at: None,
+        updated_at: None,
+        completed_at: None,
+        work_count: None,
+        assigned_agent: None,
+        project_dir: Some(project_dir.clone()),
+    };
+
+    let created = DataStore::new(graph, db)
+        .create_feature(&project_dir, &feature)
+        .await?;
+    state.sink.emit("features-updated", json!(project_dir));
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeaturePatch {
+    /// pending, in_progress or complete; shorthand for passes/inProgress
+    status: Option<String>,
+    /// Session the change was made in, recorded in the feature history
+    session_id: Option<String>,
+    #[serde(flatten)]
+    update: FeatureUpdate,
+}
+
+async fn update_feature(
+    State(state): State<AppState>,
+    Extension(agent): Extension<AuthenticatedAgent>,
+    Path((project_dir, feature_id)): Path<(String, String)>,
+    ApiJson(patch): ApiJson<FeaturePatch>,
+) -> Result<Json<graph_db::Feature>, ApiError> {
+    let mut update = patch.update;
+    match patch.status.as_deref() {
+        None => {}
+        Some("pending") => (update.passes, update.in_progress) = (Some(false), Some(false)),
+        Some("in_progress") => (update.passes, update.in_progress) = (Some(false), Some(true)),
+        Some("complete") => (update.passes, update.in_progress) = (Some(true), Some(false)),
+        Some(other) => return Err(ApiError::Validation(format!("Unknown feature status {:?}", other))),
+    }
+
+    let (db, graph) = (&state.db, &state.graph);
+    let store = DataStore::new(graph, db);
+    project_feature(&store, &project_dir, &feature_id).await?;
+
+    let author = UpdateAuthor {
+        agent: Some(agent.0),
+        session_id: patch.session_id,
+    };
+    match store.update_feature(&feature_id, update, UpdateSource::Agent, author).await? {
+        UpdateOutcome::Applied(_) => {}
+        UpdateOutcome::Locked(fields) => return Err(ApiError::Locked { feature_id, fields }),
+        UpdateOutcome::NotFound => {
+            return Err(ApiError::NotFound(format!("Feature {} not found", feature_id)))
+        }
+    }
+    state.sink.emit("features-updated", json!(project_dir));
+
+    Ok(Json(project_feature(&store, &project_dir, &feature_id).await?))
+}
+
+async fn delete_feature(
+    State(state): State<AppState>,
+    Path((project_dir, feature_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let (db, graph) = (&state.db, &state.graph);
+    let store = DataStore::new(graph, db);
+    project_feature(&store, &project_dir, &feature_id).await?;
+
+    // Rows from feature_list.json would come back on the next file sync
+    let id = feature_id.clone();
+    if db.run(move |db| db.get_graph_updated_at(&id)).await?.is_none() {
+        return Err(ApiError::Conflict(format!(
+            "Feature {} is managed by feature_list.json",
+            feature_id
+        )));
+    }
+
+    if !store.delete_feature(&feature_id, UpdateSource::Agent).await? {
+        return Err(ApiError::Conflict(format!(
+            "Feature {} was recently edited by a human; agent changes are locked",
+            feature_id
+        )));
+    }
+    state.sink.emit("features-updated", json!(project_dir));
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A feature's recorded field changes, newest first
+async fn get_feature_history(
+    State(state): State<AppState>,
+    Path((project_dir, feature_id)): Path<(String, String)>,
+    Query(query): Query<LimitQuery>,
+) -> Result<Json<Vec<FeatureChange>>, ApiError> {
+    let (db, graph) = (&state.db, &state.graph);
+    project_feature(&DataStore::new(graph, db), &project_dir, &feature_id).await?;
+
+    let limit = query.limit.unwrap_or(100);
+    let history = db.run(move |db| db.get_feature_history(&feature_id, limit)).await?;
+    Ok(Json(history))
+}
+
+/// Look up a cached feature, treating one from another project as missing
+async fn project_feature(
+    store: &DataStore<'_>,
+    project_dir: &str,
+    feature_id: &str,
+) -> Result<graph_db::Feature, ApiError> {
+    store
+        .get_cached_feature(feature_id)
+        .await?
+        .filter(|f| f.project_dir.as_deref() == Some(project_dir))
+        .ok_or_else(|| ApiError::NotFound(format!("Feature {} not found in {}", feature_id, project_dir)))
+}
+
+async fn get_sessions(
+    State(state): State<AppState>,
+    Query(query): Query<LimitQuery>,
+) -> Result<Json<Vec<graph_db::Session>>, ApiError> {
+    let (db, graph) = (&state.db, &state.graph);
+    let sessions = DataStore::new(graph, db)
+        .get_sessions(query.limit.unwrap_or(50))
+        .await?;
+    Ok(Json(sessions))
+}
+
+#[derive(Deserialize)]
+struct RulesQuery {
+    /// Global and this project's rules; global rules only when omitted
+    project_dir: Option<String>,
+}
+
+async fn get_rules(
+    State(state): State<AppState>,
+    Query(query): Query<RulesQuery>,
+) -> Result<Json<Vec<graph_db::Rule>>, ApiError> {
+    let graph = &state.graph;
+    let rules = match query.project_dir.as_deref() {
+        Some(project_dir) => graph.get_enabled_rules(project_dir).await?,
+        None => graph.get_rules_by_scope("global", None).await?,
+    };
+    Ok(Json(rules))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NewRule {
+    name: String,
+    description: String,
+    trigger: serde_json::Value,
+    action: serde_json::Value,
+    /// Project the rule applies to; the rule is global when omitted
+    project_dir: Option<String>,
+    /// hook, prompt_inject or reminder
+    enforcement: Option<String>,
+}
+
+async fn create_rule(
+    State(state): State<AppState>,
+    ApiJson(new): ApiJson<NewRule>,
+) -> Result<(StatusCode, Json<graph_db::Rule>), ApiError> {
+    if new.name.trim().is_empty() {
+        return Err(ApiError::Validation("name must not be empty".to_string()));
+    }
+
+    let project_dir = new.project_dir.filter(|p| !p.is_empty());
+    if let Some(project_dir) = &project_dir {
+        ensure_graph_project(&state, project_dir).await?;
+    }
+
+    let mut rule = graph_db::Rule {
+        id: None,
+        name: new.name,
+        description: new.description,
+        trigger: new.trigger,
+        action: new.action,
+        scope: if project_dir.is_some() { "project" } else { "global" }.to_string(),
+        enforcement: new.enforcement.unwrap_or_else(|| "reminder".to_string()),
+        enabled: Some(true),
+        created_at: None,
+        triggered_count: Some(0),
+        source_instruction_count: None,
+    };
+
+    let graph = &state.graph;
+    rule.id = Some(graph.create_rule(&rule, project_dir.as_deref()).await?);
+
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+#[derive(Deserialize)]
+struct RulePatch {
+    enabled: bool,
+}
+
+async fn update_rule(
+    State(state): State<AppState>,
+    Path(rule_id): Path<String>,
+    ApiJson(patch): ApiJson<RulePatch>,
+) -> Result<Json<ApiResponse>, ApiError> {
+    let graph = &state.graph;
+    graph.toggle_rule(&rule_id, patch.enabled).await?;
+    Ok(Json(ApiResponse { ok: true }))
+}
+
+#[derive(Deserialize)]
+struct InsightsQuery {
+    /// Substring of the insight description
+    q: Option<String>,
+    /// Comma-separated tags, any of which must match
+    tags: Option<String>,
+    limit: Option<i64>,
+}
+
+async fn get_insights(
+    State(state): State<AppState>,
+    Query(query): Query<InsightsQuery>,
+) -> Result<Json<Vec<graph_db::Insight>>, ApiError> {
+    let graph = &state.graph;
+    let limit = query.limit.unwrap_or(20);
+
+    let tags: Vec<String> = query
+        .tags
+        .iter()
+        .flat_map(|tags| tags.split(','))
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+
+    let insights = if tags.is_empty() {
+        graph.search_insights(query.q.as_deref().unwrap_or_default(), limit).await?
+    } else {
+        graph.get_insights_by_tags(&tags, limit).await?
+    };
+    Ok(Json(insights))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NewInsight {
+    description: String,
+    /// solution, anti_pattern, best_practice or tool_usage
+    pattern_type: String,
+    tags: Option<Vec<String>>,
+    /// Graph event the insight was learned from
+    event_id: Option<String>,
+}
+
+async fn create_insight(
+    State(state): State<AppState>,
+    ApiJson(new): ApiJson<NewInsight>,
+) -> Result<(StatusCode, Json<graph_db::Insight>), ApiError> {
+    if new.description.trim().is_empty() {
+        return Err(ApiError::Validation("description must not be empty".to_string()));
+    }
+
+    let mut insight = graph_db::Insight {
+        id: None,
+        description: new.description,
+        pattern_type: new.pattern_type,
+        tags: new.tags,
+        created_at: None,
+        usage_count: Some(0),
+        effectiveness_score: None,
+    };
+
+    let graph = &state.graph;
+    insight.id = Some(graph.record_insight(&insight, new.event_id.as_deref()).await?);
+
+    Ok((StatusCode::CREATED, Json(insight)))
+}
+
+/// MCP over streamable HTTP. Every request gets a plain JSON response and
+/// notifications are acknowledged with 202; the server never initiates
+/// messages, so there is no GET stream.
+async fn mcp_endpoint(
+    State(state): State<AppState>,
+    Extension(agent): Extension<AuthenticatedAgent>,
+    body: String,
+) -> Response {
+    let server = McpServer::new(Arc::clone(&state.graph), Arc::clone(&state.db)).with_agent(agent.0);
+
+    match server.handle_message(&body).await {
+        Some(response) => Json(response).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeatureUpdateEvent {
+    project_dir: String,
+    stats: FeatureStats,
+    changed_features: Vec<ChangedFeature>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FeatureStats {
+    total: i64,
+    completed: i64,
+    percentage: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChangedFeature {
+    description: String,
+    category: String,
+}
+
+async fn receive_feature_update(
+    State(state): State<AppState>,
+    Extension(agent): Extension<AuthenticatedAgent>,
+    ApiJson(update): ApiJson<FeatureUpdateEvent>,
+) -> Result<Json<ApiResponse>, ApiError> {
+    // Create events for completed features
+    for feature in update.changed_features {
+        let mut event = AgentEvent {
+            id: None,
+            event_type: "FeatureCompleted".to_string(),
+            source_agent: agent.0.clone(),
+            session_id: "feature-update".to_string(),
+            project_dir: update.project_dir.clone(),
+            tool_name: Some(feature.description.clone()),
+            payload: Some(serde_json::json!({ "category": feature.category }).to_string()),
+            feature_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            idempotency_key: None,
+            details: EventDetails::default(),
+        };
+
+        let row = event.clone();
+        event.id = Some(state.db.run(move |db| db.insert_event(&row)).await?.id());
+        let _ = state.event_tx.send(event);
+
+        // Desktop notification
+        state.sink.notify("✅ Feature Completed", &feature.description);
+    }
+
+    // Emit progress update to frontend
+    state.sink.emit("progress-update", json!(update.stats));
+
+    Ok(Json(ApiResponse { ok: true }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionStartEvent {
+    session_id: String,
+    source_agent: String,
+    project_dir: String,
+}
+
+async fn session_start(
+    State(state): State<AppState>,
+    Extension(agent): Extension<AuthenticatedAgent>,
+    ApiJson(mut incoming): ApiJson<SessionStartEvent>,
+) -> Result<Json<ApiResponse>, ApiError> {
+    incoming.source_agent = tag_agent(&agent, &incoming.source_agent);
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let session = Session {
+        session_id: incoming.session_id.clone(),
+        source_agent: incoming.source_agent.clone(),
+        project_dir: incoming.project_dir.clone(),
+        started_at: now.clone(),
+        last_activity: now.clone(),
+        status: "active".to_string(),
+    };
+
+    state.db.run(move |db| db.upsert_session(&session)).await?;
+
+    if let Err(e) = ensure_graph_project(&state, &incoming.project_dir).await {
+        tracing::warn!("Failed to upsert project in graph: {}", e);
+    }
+
+    // Auto-register project if not already watched
+    let project_dir = incoming.project_dir.clone();
+    let sink = Arc::clone(&state.sink);
+    state
+        .db
+        .run(move |db| match db.add_watched_project(&project_dir) {
+            Ok(true) => {
+                tracing::info!("Auto-registered new project: {}", project_dir);
+                // Sync features from feature_list.json if it exists
+                sync_features_from_file(db, &project_dir, sink.as_ref());
+            }
+            Ok(false) => {
+                // Project already registered, still sync features in case file changed
+                sync_features_from_file(db, &project_dir, sink.as_ref());
+            }
+            Err(e) => {
+                tracing::error!("Failed to auto-register project: {}", e);
+            }
+        })
+        .await;
+
+    // Create session start event
+    let mut event = AgentEvent {
+        id: None,
+        event_type: "SessionStart".to_string(),
+        source_agent: incoming.source_agent,
+        session_id: incoming.session_id,
+        project_dir: incoming.project_dir,
+        tool_name: None,
+        payload: None,
+        feature_id: None,
+        created_at: now,
+        idempotency_key: None,
+        details: EventDetails::default(),
+    };
+
+    let row = event.clone();
+    event.id = Some(state.db.run(move |db| db.insert_event(&row)).await?.id());
+
+    // Creates the session node and its SessionStart event
+    if let Err(e) = write_event_to_graph(&state, &event).await {
+        tracing::warn!("Failed to write session start to graph: {}", e);
+    }
+
+    let _ = state.event_tx.send(event);
+
+    Ok(Json(ApiResponse { ok: true }))
+}
+
+/// Sync features from feature_list.json file to database
+fn sync_features_from_file(db: &Database, project_dir: &str, sink: &dyn EventSink) {
+    let feature_file = PathBuf::from(project_dir).join("feature_list.json");
+
+    if !feature_file.exists() {
+        return;
+    }
+
+    let content = match std::fs::read_to_string(&feature_file) {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!("Failed to read feature_list.json: {}", e);
+            return;
+        }
+    };
+
+    let features: Vec<serde_json::Value> = match serde_json::from_str(&content) {
+        Ok(f) => f,
+        Err(e) => {
+            tracing::error!("Failed to parse feature_list.json: {}", e);
+            return;
+        }
+    };
+
+    let parsed_features: Vec<Feature> = features
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let steps = f["steps"]
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|s| s.as_str().map(String::from))
+                        .collect()
+                });
+
+            Feature {
+                id: format!("{}:{}", project_dir, i),
+                project_dir: project_dir.to_string(),
+                description: f["description"].as_str().unwrap_or("").to_string(),
+                category: f["category"].as_str().unwrap_or("functional").to_string(),
+                passes: f["passes"].as_bool().unwrap_or(false),
+                in_progress: f["inProgress"].as_bool().unwrap_or(false),
+                agent: f["agent"].as_str().map(String::from),
+                steps,
+                work_count: f["workCount"].as_i64().unwrap_or(0) as i32,
+                completion_criteria: f["completionCriteria"].as_object()
+                    .map(|_| serde_json::to_string(&f["completionCriteria"]).unwrap_or_default()),
+                updated_at: chrono::Utc::now().to_rfc3339(),
+                // Agent-managed state (defaults)
+                confidence: None,
+                model: f["model"].as_str().map(String::from),
+                is_streaming: false,
+                retry_count: 0,
+                token_cost: None,
+                has_error: false,
+                last_agent_update: None,
+                // Human override state (defaults)
+                manual_priority: f["manualPriority"].as_str().map(String::from),
+                human_override_until: None,
+            }
+        })
+        .collect();
+
+    if let Err(e) = db.sync_features(project_dir, parsed_features) {
+        tracing::error!("Failed to sync features: {}", e);
+    } else {
+        tracing::info!("Synced {} features for {}", features.len(), project_dir);
+        // Emit refresh event to frontend
+        sink.emit("features-updated", json!(project_dir));
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionEndEvent {
+    session_id: String,
+    #[serde(default)]
+    source_agent: Option<String>,
+    #[serde(default)]
+    project_dir: Option<String>,
+}
+
+async fn session_end(
+    State(state): State<AppState>,
+    Extension(agent): Extension<AuthenticatedAgent>,
+    ApiJson(incoming): ApiJson<SessionEndEvent>,
+) -> Result<Json<ApiResponse>, ApiError> {
+    // Update session status
+    let session_id = incoming.session_id.clone();
+    let ended = state
+        .db
+        .run(move |db| db.update_session_status(&session_id, "ended"))
+        .await;
+    if let Err(e) = ended {
+         tracing::error!("Failed to update session status: {}", e);
+         // Continue to log event even if status update fails
+    }
+
+    let source = tag_agent(&agent, incoming.source_agent.as_deref().unwrap_or_default());
+    let project = incoming.project_dir.unwrap_or_default();
+
+    let mut event = AgentEvent {
+        id: None,
+        event_type: "SessionEnd".to_string(),
+        source_agent: source,
+        session_id: incoming.session_id,
+        project_dir: project,
+        tool_name: None,
+        payload: None,
+        feature_id: None,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        idempotency_key: None,
+        details: EventDetails::default(),
+    };
+
+    let row = event.clone();
+    event.id = Some(state.db.run(move |db| db.insert_event(&row)).await?.id());
+
+    if !event.project_dir.is_empty() {
+        if let Err(e) = write_event_to_graph(&state, &event).await {
+            tracing::warn!("Failed to write session end to graph: {}", e);
+        }
+    }
+
+    let graph = &state.graph;
+    if let Err(e) = graph.end_session(&event.session_id).await {
+        tracing::warn!("Failed to end session in graph: {}", e);
+    }
+
+    let _ = state.event_tx.send(event);
+
+    Ok(Json(ApiResponse { ok: true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::CLAUDE_CODE_AGENT;
+    use crate::db::fixtures::{event, graph_feature};
+    use crate::events::LogSink;
+    use axum::body::{to_bytes, Body};
+    use futures_util::FutureExt;
+    use tower::ServiceExt;
+
+    struct TestServer {
+        router: Router,
+        state: AppState,
+        db: Arc<Database>,
+        token: String,
+        dir: PathBuf,
+    }
+
+    impl TestServer {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("ijoka-server-{}", uuid::Uuid::new_v4()));
+            let db = Arc::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+            let tokens = TokenStore::open(dir.join("tokens.json"));
+            let token = tokens.ensure_token(CLAUDE_CODE_AGENT).unwrap();
+            let (event_tx, _) = broadcast::channel(16);
+
+            let state = AppState {
+                db: Arc::clone(&db),
+                graph: Arc::new(GraphDb::new()),
+                sink: Arc::new(LogSink),
+                event_tx: Arc::new(event_tx),
+                graph_projects: Arc::new(Mutex::new(HashSet::new())),
+                tokens: Arc::new(tokens),
+            };
+            Self {
+                router: build_router(state.clone()),
+                state,
+                db,
+                token,
+                dir,
+            }
+        }
+
+        async fn send(&self, method: Method, uri: &str, body: Option<serde_json::Value>) -> (StatusCode, serde_json::Value) {
+            let request = axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::AUTHORIZATION, format!("Bearer {}", self.token))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+                .unwrap();
+
+            let response = self.router.clone().oneshot(request).await.unwrap();
+            let status = response.status();
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+        }
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agent_edits_are_refused_under_a_human_lock() {
+        let server = TestServer::new();
+        server.db.sync_feature_from_graph(&graph_feature("f1", "/a")).unwrap();
+        let update = FeatureUpdate {
+            passes: Some(true),
+            ..Default::default()
+        };
+        server
+            .db
+            .update_feature("f1", update, UpdateSource::Human, &UpdateAuthor::default())
+            .unwrap();
+
+        let (status, body) = server
+            .send(Method::PATCH, "/projects/%2Fa/features/f1", Some(json!({ "status": "pending" })))
+            .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "fields_locked");
+        assert_eq!(body["fields"], json!(["status"]));
+
+        let (status, body) = server.send(Method::DELETE, "/projects/%2Fa/features/f1", None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "conflict");
+
+        assert!(server.db.get_feature("f1").unwrap().unwrap().passes);
+    }
+
+    #[tokio::test]
+    async fn test_feature_list_rows_cannot_be_deleted() {
+        let server = TestServer::new();
+        let project = server.dir.join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("feature_list.json"), r#"[{"description": "From the file"}]"#).unwrap();
+
+        let project_dir = project.display().to_string();
+        sync_features_from_file(&server.db, &project_dir, &LogSink);
+        let feature_id = format!("{}:0", project_dir);
+
+        let uri = format!(
+            "/projects/{}/features/{}",
+            urlencoding::encode(&project_dir),
+            urlencoding::encode(&feature_id)
+        );
+        let (status, body) = server.send(Method::DELETE, &uri, None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body["error"].as_str().unwrap().contains("feature_list.json"));
+        assert!(server.db.get_feature(&feature_id).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_features_from_another_project_are_not_found() {
+        let server = TestServer::new();
+        server.db.sync_feature_from_graph(&graph_feature("f1", "/a")).unwrap();
+
+        let (status, body) = server
+            .send(Method::PATCH, "/projects/%2Fb/features/f1", Some(json!({ "status": "complete" })))
+            .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "not_found");
+
+        let (status, _) = server.send(Method::DELETE, "/projects/%2Fb/features/f1", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = server.send(Method::GET, "/projects/%2Fb/features/f1/history", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let cached = server.db.get_feature("f1").unwrap().unwrap();
+        assert!(!cached.passes);
+    }
+
+    #[tokio::test]
+    async fn test_event_tail_keeps_events_across_cancelled_polls() {
+        let server = TestServer::new();
+        let total = STREAM_REPLAY_BATCH + 1;
+        let events: Vec<_> = (0..total).map(|n| event("Bash", "/a", &n.to_string())).collect();
+        server.db.insert_events(&events).unwrap();
+
+        let mut tail = EventTail::new(&server.state, EventFilter::default(), Some(0)).await;
+        let mut ids = Vec::new();
+        while (ids.len() as i64) < total {
+            // Poll once and drop the call, as a losing select! branch would
+            let event = match tail.next().now_or_never() {
+                Some(event) => event,
+                None => tokio::time::timeout(Duration::from_secs(5), tail.next())
+                    .await
+                    .expect("a dropped poll lost an event"),
+            };
+            ids.push(event.unwrap().id.unwrap());
+        }
+        assert_eq!(ids, (1..=total).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_busy_port_is_retried_until_free() {
+        let blocker = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = blocker.local_addr().unwrap().port();
+        let server = TestServer::new();
+        let control = Arc::new(ServerControl::new(port));
+
+        let serving = Arc::clone(&control);
+        let router = server.router.clone();
+        let task = tokio::spawn(async move { serve_tcp(router, "127.0.0.1", &serving, &LogSink).await });
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let status = control.status();
+        assert!(!status.listening);
+        assert!(status.error.is_some());
+
+        // The port frees up without any port change; the retry picks it up
+        drop(blocker);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !control.status().listening {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("port was not retried");
+        assert_eq!(control.status().error, None);
+
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_batch_events_are_broadcast_in_order() {
+        let server = TestServer::new();
+        let mut rx = server.state.event_tx.subscribe();
+
+        let batch: Vec<_> = ["one", "two", "three"]
+            .iter()
+            .map(|prompt| {
+                serde_json::json!({
+                    "eventType": "UserPromptSubmit",
+                    "sourceAgent": CLAUDE_CODE_AGENT,
+                    "sessionId": "s1",
+                    "projectDir": "/a",
+                    "payload": { "prompt": prompt },
+                })
+            })
+            .collect();
+        let (status, body) = server.send(Method::POST, "/events/batch", Some(batch.into())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["accepted"], 3);
+
+        // Delivered straight after the insert, whatever the graph mirror does
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            ids.push(rx.try_recv().unwrap().id.unwrap());
+        }
+        assert_eq!(ids, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,550 @@
+//! Data Access Layer
+//!
+//! Serves read queries from the graph database when it is connected and
+//! transparently falls back to the SQLite cache when Memgraph is unavailable.
+//! SQLite rows are mapped into the graph response shapes so the frontend
+//! receives the same JSON regardless of which store answered.
+//!
+//! Feature updates are written to the SQLite cache and pushed back to the
+//! graph, refusing edits made against a stale view of the graph.
+//!
+//! SQLite calls run on the blocking pool via `Database::run`.
+
+use crate::db::{self, Database, FeatureChange, FeatureUpdate, UpdateAuthor, UpdateOutcome, UpdateSource};
+use crate::graph_db::{self, FeatureChanged, GraphDb, StatusLocked};
+use anyhow::Result;
+use std::sync::Arc;
+
+/// An edit was refused because the graph feature changed since it was last
+/// synced into the cache
+#[derive(Debug)]
+pub struct StaleFeature(pub String);
+
+impl std::fmt::Display for StaleFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Conflict: feature {} was changed in the graph since it was last synced",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for StaleFeature {}
+
+/// Facade over the graph database and the SQLite cache
+pub struct DataStore<'a> {
+    graph: &'a GraphDb,
+    sqlite: &'a Arc<Database>,
+}
+
+impl<'a> DataStore<'a> {
+    pub fn new(graph: &'a GraphDb, sqlite: &'a Arc<Database>) -> Self {
+        Self { graph, sqlite }
+    }
+
+    /// Get features for a project
+    pub async fn get_features(&self, project_dir: &str) -> Result<Vec<graph_db::Feature>> {
+        if self.graph.is_connected().await {
+            return self.graph.get_features_for_project(project_dir).await;
+        }
+
+        let project_dir = project_dir.to_string();
+        let features = self
+            .sqlite
+            .run(move |db| db.get_features(Some(&project_dir)))
+            .await?;
+        Ok(features.into_iter().map(feature_from_cache).collect())
+    }
+
+    /// Get recent events across all projects
+    pub async fn get_events(&self, limit: i64) -> Result<Vec<graph_db::Event>> {
+        if self.graph.is_connected().await {
+            return self.graph.get_all_recent_events(limit).await;
+        }
+
+        let events = self.sqlite.run(move |db| db.get_events(limit)).await?;
+        Ok(events.into_iter().map(event_from_cache).collect())
+    }
+
+    /// Get events linked to a feature
+    pub async fn get_feature_events(
+        &self,
+        feature_id: &str,
+        limit: i64,
+    ) -> Result<Vec<graph_db::Event>> {
+        if self.graph.is_connected().await {
+            return self.graph.get_events_by_feature(feature_id, limit).await;
+        }
+
+        let feature_id = feature_id.to_string();
+        let events = self
+            .sqlite
+            .run(move |db| db.get_events_by_feature(&feature_id, limit))
+            .await?;
+        Ok(events.into_iter().map(event_from_cache).collect())
+    }
+
+    /// Get the most recently active sessions
+    pub async fn get_sessions(&self, limit: i64) -> Result<Vec<graph_db::Session>> {
+        if self.graph.is_connected().await {
+            return self.graph.get_all_sessions(limit).await;
+        }
+
+        let sessions = self.sqlite.run(move |db| db.get_recent_sessions(limit)).await?;
+        Ok(sessions.into_iter().map(session_from_cache).collect())
+    }
+
+    /// Get feature statistics for a project
+    pub async fn get_stats(&self, project_path: &str) -> Result<graph_db::ProjectStats> {
+        if self.graph.is_connected().await {
+            return self.graph.get_project_stats(project_path).await;
+        }
+
+        let project_path = project_path.to_string();
+        let stats = self
+            .sqlite
+            .run(move |db| db.get_stats(Some(&project_path)))
+            .await?;
+        Ok(stats_from_cache(stats))
+    }
+
+    /// Get all known project paths
+    pub async fn get_projects(&self) -> Result<Vec<String>> {
+        if self.graph.is_connected().await {
+            let projects = self.graph.get_projects().await?;
+            return Ok(projects.into_iter().map(|p| p.path).collect());
+        }
+
+        Ok(self.sqlite.run(|db| db.get_projects()).await?)
+    }
+
+    /// Update a feature in the SQLite cache and push status changes to the
+    /// graph, where each move is also recorded as a StatusChange. Locks taken
+    /// by a human edit are mirrored to the graph.
+    ///
+    /// The status is pushed before the cache is touched, so a refused push
+    /// leaves the cache and its history as they were; offline the push is
+    /// queued in the outbox and the update applies. Fails with a conflict if
+    /// the graph feature changed since it was last synced into the cache (for
+    /// a queued push, the graph keeps its status on replay). Agent updates
+    /// touching a field a human has locked are refused with
+    /// `UpdateOutcome::Locked`.
+    pub async fn update_feature(
+        &self,
+        feature_id: &str,
+        update: FeatureUpdate,
+        source: UpdateSource,
+        author: UpdateAuthor,
+    ) -> Result<UpdateOutcome> {
+        // None for feature_list.json rows, which have no graph counterpart
+        let synced_at = self.graph_updated_at(feature_id).await?;
+        let status_changed = update.passes.is_some() || update.in_progress.is_some();
+        let fields = update.fields();
+
+        if let Some(synced_at) = synced_at.filter(|_| status_changed) {
+            let id = feature_id.to_string();
+            let Some(cached) = self.sqlite.run(move |db| db.get_feature(&id)).await? else {
+                return Ok(UpdateOutcome::NotFound);
+            };
+
+            if matches!(source, UpdateSource::Agent) {
+                let id = feature_id.to_string();
+                let mut locked: Vec<String> = self
+                    .sqlite
+                    .run(move |db| db.get_feature_locks(&id))
+                    .await?
+                    .into_iter()
+                    .map(|lock| lock.field)
+                    .collect();
+                // A lock mirrored from another client may not be in our cache yet
+                if self.graph.is_connected().await {
+                    locked.extend(self.graph.get_locked_fields(feature_id).await?);
+                }
+                locked.retain(|field| fields.contains(&field.as_str()));
+                locked.sort();
+                locked.dedup();
+                if !locked.is_empty() {
+                    return Ok(UpdateOutcome::Locked(locked));
+                }
+            }
+
+            // 0 means the row was cached before the graph reported a time
+            let expected = Some(synced_at).filter(|at| *at > 0);
+            let pushed = if update.passes.unwrap_or(cached.passes) {
+                self.graph.complete_feature(feature_id, source, expected).await
+            } else {
+                let status = if update.in_progress.unwrap_or(cached.in_progress) {
+                    "in_progress"
+                } else {
+                    "pending"
+                };
+                self.graph
+                    .update_feature_status(feature_id, status, source, expected)
+                    .await
+            };
+            match pushed {
+                Err(e) if e.is::<StatusLocked>() => {
+                    return Ok(UpdateOutcome::Locked(vec!["status".to_string()]));
+                }
+                Err(e) if e.is::<FeatureChanged>() => {
+                    return Err(StaleFeature(feature_id.to_string()).into());
+                }
+                pushed => pushed?,
+            }
+        }
+
+        let id = feature_id.to_string();
+        let changes = match self
+            .sqlite
+            .run(move |db| db.update_feature(&id, update, source, &author))
+            .await?
+        {
+            UpdateOutcome::Applied(changes) => changes,
+            refused => return Ok(refused),
+        };
+
+        if synced_at.is_none() {
+            return Ok(UpdateOutcome::Applied(changes));
+        }
+
+        // The edit is applied in both stores from here on; a failure to
+        // mirror its history or locks is logged rather than reported
+        if let Some(change) = changes.iter().find(|change| change.field == "status") {
+            if let Err(e) = self.graph.record_status_change(&status_change(change)).await {
+                tracing::warn!("Failed to record status change for feature {}: {}", feature_id, e);
+            }
+        }
+
+        // Mirror the locks this edit took so graph-side writers respect them
+        if matches!(source, UpdateSource::Human) {
+            let id = feature_id.to_string();
+            let locks = self.sqlite.run(move |db| db.get_feature_locks(&id)).await?;
+            let locked: Vec<_> = locks
+                .iter()
+                .filter(|lock| fields.contains(&lock.field.as_str()))
+                .collect();
+            if let Some(lock) = locked.first() {
+                let fields: Vec<String> = locked.iter().map(|lock| lock.field.clone()).collect();
+                if let Err(e) = self
+                    .graph
+                    .lock_fields(feature_id, &fields, lock.locked_until.as_deref())
+                    .await
+                {
+                    tracing::warn!("Failed to mirror locks for feature {}: {}", feature_id, e);
+                }
+            }
+        }
+
+        // Our own push bumped the graph's updated_at; record it so the next
+        // edit isn't reported as a conflict
+        if status_changed && self.graph.is_connected().await {
+            if let Some(changed_at) = self.graph.get_feature_changed_at(feature_id).await? {
+                let id = feature_id.to_string();
+                self.sqlite
+                    .run(move |db| db.set_graph_updated_at(&id, changed_at))
+                    .await?;
+            }
+        }
+
+        Ok(UpdateOutcome::Applied(changes))
+    }
+
+    /// Get a feature from the SQLite cache, in the graph response shape
+    pub async fn get_cached_feature(&self, feature_id: &str) -> Result<Option<graph_db::Feature>> {
+        let id = feature_id.to_string();
+        let feature = self.sqlite.run(move |db| db.get_feature(&id)).await?;
+        Ok(feature.map(feature_from_cache))
+    }
+
+    /// Create a feature in the graph and mirror it into the cache so it is
+    /// visible before the next graph sync. The project node must exist.
+    pub async fn create_feature(
+        &self,
+        project_dir: &str,
+        feature: &graph_db::Feature,
+    ) -> Result<graph_db::Feature> {
+        // Offline creates are queued in the outbox
+        let feature_id = self.graph.create_feature(feature, project_dir).await?;
+
+        let graph_updated_at = if self.graph.is_connected().await {
+            self.graph.get_feature_changed_at(&feature_id).await?.unwrap_or(0)
+        } else {
+            0
+        };
+
+        let sync = db::GraphFeatureSync {
+            id: feature_id.clone(),
+            project_dir: project_dir.to_string(),
+            description: feature.description.clone(),
+            category: feature.category.clone(),
+            status: feature.status.clone(),
+            steps: feature.steps.clone().unwrap_or_default(),
+            graph_updated_at,
+        };
+        self.sqlite.run(move |db| db.sync_feature_from_graph(&sync)).await?;
+
+        let mut created = self
+            .get_cached_feature(&feature_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Feature {} missing from cache after create", feature_id))?;
+        created.priority = feature.priority;
+        Ok(created)
+    }
+
+    /// Delete a feature from the cache and the graph. Returns false if it does
+    /// not exist or an agent delete was blocked by a human override.
+    pub async fn delete_feature(&self, feature_id: &str, source: UpdateSource) -> Result<bool> {
+        let synced_at = self.graph_updated_at(feature_id).await?;
+
+        let id = feature_id.to_string();
+        if !self
+            .sqlite
+            .run(move |db| db.delete_feature(&id, source))
+            .await?
+        {
+            return Ok(false);
+        }
+
+        if synced_at.is_some() {
+            self.graph.delete_feature(feature_id).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Release human locks on a feature (the given fields, or all of them) in
+    /// the cache and the graph. Returns the number of locks removed.
+    pub async fn release_override(&self, feature_id: &str, fields: Option<Vec<String>>) -> Result<usize> {
+        let synced_at = self.graph_updated_at(feature_id).await?;
+
+        let id = feature_id.to_string();
+        let graph_fields = fields.clone();
+        let released = self
+            .sqlite
+            .run(move |db| db.release_override(&id, fields.as_deref()))
+            .await?;
+
+        if synced_at.is_some() {
+            self.graph
+                .release_field_locks(feature_id, graph_fields.as_deref())
+                .await?;
+        }
+
+        Ok(released)
+    }
+
+    async fn graph_updated_at(&self, feature_id: &str) -> Result<Option<i64>> {
+        let id = feature_id.to_string();
+        Ok(self.sqlite.run(move |db| db.get_graph_updated_at(&id)).await?)
+    }
+}
+
+// =============================================================================
+// CACHE -> GRAPH SHAPE MAPPING
+// =============================================================================
+
+fn status_change(change: &FeatureChange) -> graph_db::StatusChange {
+    graph_db::StatusChange {
+        id: uuid::Uuid::new_v4().to_string(),
+        feature_id: change.feature_id.clone(),
+        from_status: change.old_value.clone().unwrap_or_default(),
+        to_status: change.new_value.clone().unwrap_or_default(),
+        source: change.source.clone(),
+        agent: change.agent.clone(),
+        session_id: change.session_id.clone(),
+        changed_at: change.changed_at.clone(),
+    }
+}
+
+fn feature_from_cache(feature: db::Feature) -> graph_db::Feature {
+    let status = if feature.passes {
+        "complete"
+    } else if feature.in_progress {
+        "in_progress"
+    } else {
+        "pending"
+    };
+
+    graph_db::Feature {
+        id: Some(feature.id),
+        description: feature.description,
+        category: feature.category,
+        status: status.to_string(),
+        passes: feature.passes,
+        in_progress: feature.in_progress && !feature.passes,
+        priority: None,
+        steps: feature.steps,
+        created_at: None,
+        updated_at: Some(feature.updated_at),
+        completed_at: None,
+        work_count: Some(feature.work_count),
+        assigned_agent: feature.agent,
+        project_dir: Some(feature.project_dir),
+    }
+}
+
+fn event_from_cache(event: db::AgentEvent) -> graph_db::Event {
+    // SQLite stores payloads as text; keep non-JSON payloads as plain strings
+    let payload = event.payload.map(|p| {
+        serde_json::from_str(&p).unwrap_or(serde_json::Value::String(p))
+    });
+
+    graph_db::Event {
+        id: event.id.map(|id| id.to_string()),
+        event_type: event.event_type,
+        tool_name: event.tool_name,
+        payload,
+        summary: event.details.summary,
+        timestamp: Some(event.created_at),
+        success: event.details.success,
+        tool_input: event
+            .details
+            .tool_input
+            .and_then(|input| serde_json::from_str(&input).ok()),
+        exit_code: event.details.exit_code,
+        duration_ms: event.details.duration_ms,
+        source_agent: Some(event.source_agent),
+        session_id: Some(event.session_id),
+        project_path: Some(event.project_dir),
+        feature_id: event.feature_id,
+        feature_description: None,
+    }
+}
+
+fn session_from_cache(session: db::Session) -> graph_db::Session {
+    graph_db::Session {
+        ended_at: (session.status == "ended").then(|| session.last_activity.clone()),
+        id: session.session_id,
+        agent: session.source_agent,
+        status: session.status,
+        started_at: Some(session.started_at),
+        last_activity: Some(session.last_activity),
+        event_count: None,
+        is_subagent: None,
+    }
+}
+
+fn stats_from_cache(stats: db::Stats) -> graph_db::ProjectStats {
+    graph_db::ProjectStats {
+        total: stats.total as i32,
+        completed: stats.completed as i32,
+        in_progress: stats.in_progress as i32,
+        percentage: stats.percentage as i32,
+        active_sessions: stats.active_sessions as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{self, graph_feature};
+    use crate::db::{AgentEvent, EventDetails, GraphFeatureSync};
+    use std::path::Path;
+
+    fn cached_feature(id: &str, status: &str) -> GraphFeatureSync {
+        GraphFeatureSync {
+            status: status.to_string(),
+            ..graph_feature(id, "/a")
+        }
+    }
+
+    fn event(payload: &str) -> AgentEvent {
+        AgentEvent {
+            details: EventDetails {
+                tool_input: Some(r#"{"command":"ls"}"#.to_string()),
+                ..Default::default()
+            },
+            ..fixtures::event("Bash", "/a", payload)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reads_fall_back_to_cache_shapes() {
+        let sqlite = Arc::new(Database::new(Path::new(":memory:")).unwrap());
+        sqlite.sync_feature_from_graph(&cached_feature("f1", "complete")).unwrap();
+        sqlite.sync_feature_from_graph(&cached_feature("f2", "in_progress")).unwrap();
+        sqlite.insert_event(&event(r#"{"exit":0}"#)).unwrap();
+        sqlite.insert_event(&event("not json")).unwrap();
+
+        // Never connected, so every read is served from SQLite
+        let graph = GraphDb::new();
+        let store = DataStore::new(&graph, &sqlite);
+
+        let mut features = store.get_features("/a").await.unwrap();
+        features.sort_by(|a, b| a.id.cmp(&b.id));
+        let shapes: Vec<_> = features
+            .iter()
+            .map(|f| (f.id.as_deref().unwrap(), f.status.as_str(), f.passes, f.in_progress))
+            .collect();
+        assert_eq!(shapes, [("f1", "complete", true, false), ("f2", "in_progress", false, true)]);
+        assert_eq!(features[0].project_dir.as_deref(), Some("/a"));
+        assert_eq!(features[0].steps.as_deref(), Some(&["Write the login form".to_string()][..]));
+
+        let events = store.get_events(10).await.unwrap();
+        assert_eq!(events.len(), 2);
+        let payloads: Vec<_> = events.iter().map(|e| e.payload.clone().unwrap()).collect();
+        assert!(payloads.contains(&serde_json::json!({ "exit": 0 })));
+        assert!(payloads.contains(&serde_json::json!("not json")));
+        assert!(events.iter().all(|e| e.tool_input == Some(serde_json::json!({ "command": "ls" }))));
+        assert_eq!(events[0].project_path.as_deref(), Some("/a"));
+    }
+
+    #[tokio::test]
+    async fn test_refused_status_push_leaves_the_cache_untouched() {
+        let sqlite = Arc::new(Database::new(Path::new(":memory:")).unwrap());
+        sqlite.sync_feature_from_graph(&graph_feature("f1", "/a")).unwrap();
+        let complete = || FeatureUpdate {
+            passes: Some(true),
+            ..Default::default()
+        };
+
+        // A human lock that reached the graph but not our cache
+        let graph = GraphDb::new();
+        graph.refuse_status_writes(|id| StatusLocked(id).into());
+        let store = DataStore::new(&graph, &sqlite);
+        let outcome = store
+            .update_feature("f1", complete(), UpdateSource::Agent, UpdateAuthor::default())
+            .await
+            .unwrap();
+        assert!(matches!(outcome, UpdateOutcome::Locked(ref fields) if fields == &["status"]));
+
+        // Another client moved the feature after our last sync
+        graph.refuse_status_writes(|id| FeatureChanged(id).into());
+        let err = store
+            .update_feature("f1", complete(), UpdateSource::Human, UpdateAuthor::default())
+            .await
+            .unwrap_err();
+        assert!(err.is::<StaleFeature>());
+
+        assert!(!sqlite.get_feature("f1").unwrap().unwrap().passes);
+        assert!(sqlite.get_feature_history("f1", 10).unwrap().is_empty());
+        assert!(sqlite.get_feature_locks("f1").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_offline_status_push_is_queued_with_the_sync_point() {
+        let sqlite = Arc::new(Database::new(Path::new(":memory:")).unwrap());
+        sqlite.sync_feature_from_graph(&graph_feature("f1", "/a")).unwrap();
+        let graph = GraphDb::new().with_outbox(Arc::clone(&sqlite));
+        let store = DataStore::new(&graph, &sqlite);
+
+        let update = FeatureUpdate {
+            passes: Some(true),
+            ..Default::default()
+        };
+        let outcome = store
+            .update_feature("f1", update, UpdateSource::Human, UpdateAuthor::default())
+            .await
+            .unwrap();
+        assert!(matches!(outcome, UpdateOutcome::Applied(_)));
+        assert!(sqlite.get_feature("f1").unwrap().unwrap().passes);
+
+        let pending = sqlite.get_pending_outbox(10).unwrap();
+        let pushed: graph_db::GraphMutation = serde_json::from_str(&pending[0].payload).unwrap();
+        assert!(matches!(
+            pushed,
+            graph_db::GraphMutation::CompleteFeature { human: true, expected_changed_at: Some(1), .. }
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+//! Graph -> SQLite Sync
+//!
+//! Keeps the SQLite cache in step with the graph database: an incremental
+//! sync after every (re)connect, then a periodic poll so the UI sees changes
+//! hooks write straight to Memgraph.
+
+use crate::db::{Database, GraphFeatureSync};
+use crate::events::EventSink;
+use crate::graph_db::GraphDb;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Report graph connection transitions and resync the cache on each connect
+pub async fn forward_graph_status(graph_db: Arc<GraphDb>, sqlite_db: Arc<Database>, sink: Arc<dyn EventSink>) {
+    let mut graph_status = graph_db.subscribe_status();
+    loop {
+        let connected = *graph_status.borrow_and_update();
+        sink.emit("graph-db-connected", json!(connected));
+
+        if connected {
+            tracing::info!("Connected to Memgraph graph database");
+
+            // Sync graph data to SQLite cache immediately after (re)connection
+            match sync_graph_to_sqlite(&graph_db, &sqlite_db).await {
+                Ok(changed_ids) => {
+                    tracing::info!(
+                        "Graph->SQLite sync on connect: {} features changed",
+                        changed_ids.len()
+                    );
+                    sink.emit("cache-synced", json!(changed_ids.len()));
+                }
+                Err(e) => {
+                    tracing::warn!("Sync on connect failed: {}", e);
+                }
+            }
+        } else {
+            tracing::info!("Graph database unavailable. Using SQLite-only mode.");
+        }
+
+        if graph_status.changed().await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Periodic Memgraph -> SQLite sync (every 5 seconds) while connected
+pub async fn run_periodic_sync(graph_db: Arc<GraphDb>, sqlite_db: Arc<Database>, sink: Arc<dyn EventSink>) {
+    // Wait a bit for initial connection
+    tokio::time::sleep(Duration::from_secs(3)).await;
+
+    let mut interval = tokio::time::interval(Duration::from_secs(5));
+    loop {
+        interval.tick().await;
+
+        // Only sync if graph DB is connected
+        if !graph_db.is_connected().await {
+            continue;
+        }
+
+        match sync_graph_to_sqlite(&graph_db, &sqlite_db).await {
+            Ok(changed_ids) if !changed_ids.is_empty() => {
+                tracing::debug!("Graph->SQLite sync: {} features changed", changed_ids.len());
+                // Notify frontend to refresh only when something changed
+                sink.emit("features-updated", json!(changed_ids));
+            }
+            Err(e) => {
+                tracing::debug!("Graph sync skipped: {}", e);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Incrementally sync features from Graph DB to SQLite cache.
+/// Only features updated since the stored watermark are fetched; deletions and
+/// project moves are reconciled against the graph's full feature membership.
+/// Returns the IDs of features whose cached row actually changed.
+pub async fn sync_graph_to_sqlite(
+    graph_db: &GraphDb,
+    sqlite_db: &Arc<Database>,
+) -> Result<Vec<String>, String> {
+    if !graph_db.is_connected().await {
+        return Err("Graph database not connected".to_string());
+    }
+
+    let since = sqlite_db
+        .run(|db| db.get_graph_sync_watermark())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let project_paths: Vec<String> = graph_db
+        .get_projects()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|p| p.path)
+        .collect();
+
+    let changes = graph_db
+        .get_features_changed_since(&project_paths, since)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let features: Vec<GraphFeatureSync> = changes
+        .features
+        .into_iter()
+        .filter_map(|(feature, graph_updated_at)| {
+            Some(GraphFeatureSync {
+                id: feature.id?,
+                project_dir: feature.project_dir?,
+                description: feature.description,
+                category: feature.category,
+                status: feature.status,
+                steps: feature.steps.unwrap_or_default(),
+                graph_updated_at,
+            })
+        })
+        .collect();
+    let mut watermark = changes.watermark;
+
+    let mut changed_ids = sqlite_db
+        .run(move |db| {
+            let mut changed_ids = Vec::new();
+            for sync_feature in &features {
+                match db.sync_feature_from_graph(sync_feature) {
+                    Ok(true) => changed_ids.push(sync_feature.id.clone()),
+                    Ok(false) => {}
+                    // Keep the old watermark so the failed feature is retried next time
+                    Err(e) => return Err(format!("Failed to sync feature {}: {}", sync_feature.id, e)),
+                }
+
+                // A human lock held the graph's status back: stop the watermark
+                // short of it so the change is pulled again after the lock ends
+                let synced = db
+                    .get_graph_updated_at(&sync_feature.id)
+                    .map_err(|e| e.to_string())?;
+                if synced.is_some_and(|synced| synced < sync_feature.graph_updated_at) {
+                    watermark = watermark.min(sync_feature.graph_updated_at - 1);
+                }
+            }
+
+            db.set_graph_sync_watermark(watermark)
+                .map_err(|e| e.to_string())?;
+            Ok(changed_ids)
+        })
+        .await?;
+
+    // Re-parenting or deleting a feature doesn't bump its updated_at, so compare
+    // the whole membership instead of relying on the watermark
+    let memberships = graph_db
+        .get_feature_memberships()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let reconciled = sqlite_db
+        .run(move |db| db.reconcile_graph_features(&memberships))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    changed_ids.extend(reconciled.deleted);
+    changed_ids.extend(reconciled.moved);
+    changed_ids.sort();
+    changed_ids.dedup();
+
+    Ok(changed_ids)
+}
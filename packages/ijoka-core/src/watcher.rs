@@ -14,18 +14,19 @@
 //!
 //! TODO: Remove feature_list.json watching after MCP server implementation (Phase 2)
 
-use crate::db::{AgentEvent, DbState, Feature};
+use crate::db::{AgentEvent, Database, EventDetails, Feature};
+use crate::events::EventSink;
 use notify::RecursiveMode;
 use notify_debouncer_mini::new_debouncer;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
-use tauri::{Emitter, Manager};
 use tokio::sync::broadcast;
 
 pub fn start_watching(
-    app: tauri::AppHandle,
+    db: Arc<Database>,
+    sink: Arc<dyn EventSink>,
     event_tx: Arc<broadcast::Sender<AgentEvent>>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let home = dirs::home_dir().ok_or("Could not find home directory")?;
@@ -46,8 +47,7 @@ pub fn start_watching(
     }
 
     // Load config and watch configured project directories
-    let db: tauri::State<DbState> = app.state();
-    if let Ok(config) = db.0.get_config() {
+    if let Ok(config) = db.get_config() {
         for project in &config.watched_projects {
             let feature_file = PathBuf::from(project).join("feature_list.json");
             if let Some(parent) = feature_file.parent() {
@@ -67,7 +67,7 @@ pub fn start_watching(
         match result {
             Ok(events) => {
                 for event in events {
-                    handle_file_event(&app, &event_tx, &event.path);
+                    handle_file_event(&db, sink.as_ref(), &event_tx, &event.path);
                 }
             }
             Err(e) => tracing::error!("Watch error: {:?}", e),
@@ -78,7 +78,8 @@ pub fn start_watching(
 }
 
 fn handle_file_event(
-    app: &tauri::AppHandle,
+    db: &Database,
+    sink: &dyn EventSink,
     event_tx: &broadcast::Sender<AgentEvent>,
     path: &Path,
 ) {
@@ -90,17 +91,17 @@ fn handle_file_event(
     // The watcher's transcript filename-based session_id was causing mismatches.
     //
     // if path_str.ends_with(".jsonl") && path_str.contains(".claude/projects") {
-    //     handle_transcript_change(app, event_tx, path);
+    //     handle_transcript_change(db, event_tx, path);
     // }
 
     // Handle feature_list.json changes
     if path_str.ends_with("feature_list.json") {
-        handle_feature_list_change(app, event_tx, path);
+        handle_feature_list_change(db, sink, event_tx, path);
     }
 }
 
 fn handle_transcript_change(
-    app: &tauri::AppHandle,
+    db: &Database,
     event_tx: &broadcast::Sender<AgentEvent>,
     path: &Path,
 ) {
@@ -137,7 +138,7 @@ fn handle_transcript_change(
         return;
     }
 
-    let event = AgentEvent {
+    let mut event = AgentEvent {
         id: None,
         event_type: "TranscriptUpdated".to_string(),
         source_agent: "claude-code".to_string(),
@@ -147,11 +148,12 @@ fn handle_transcript_change(
         payload,
         feature_id,
         created_at: chrono::Utc::now().to_rfc3339(),
+        idempotency_key: None,
+        details: EventDetails::default(),
     };
 
     // Store in database
-    let db: tauri::State<DbState> = app.state();
-    let _ = db.0.insert_event(&event);
+    event.id = db.insert_event(&event).ok().map(|inserted| inserted.id());
 
     // Broadcast to frontend
     let _ = event_tx.send(event);
@@ -412,7 +414,8 @@ fn get_active_feature_id(project_dir: &str) -> Option<String> {
 }
 
 fn handle_feature_list_change(
-    app: &tauri::AppHandle,
+    db: &Database,
+    sink: &dyn EventSink,
     event_tx: &broadcast::Sender<AgentEvent>,
     path: &Path,
 ) {
@@ -437,10 +440,8 @@ fn handle_feature_list_change(
         }
     };
 
-    let db: tauri::State<DbState> = app.state();
-
     // Get old features to detect changes
-    let old_features = db.0.get_features(Some(&project_dir)).unwrap_or_default();
+    let old_features = db.get_features(Some(&project_dir)).unwrap_or_default();
     let old_completed: HashSet<String> = old_features
         .iter()
         .filter(|f| f.passes)
@@ -527,7 +528,7 @@ fn handle_feature_list_change(
 
             if !was_complete_in_db {
                 // New completion from JSON file
-                let event = AgentEvent {
+                let mut event = AgentEvent {
                     id: None,
                     event_type: "FeatureCompleted".to_string(),
                     source_agent: feature
@@ -545,25 +546,22 @@ fn handle_feature_list_change(
                     ),
                     feature_id: Some(feature.id.clone()),
                     created_at: chrono::Utc::now().to_rfc3339(),
+                    idempotency_key: None,
+                    details: EventDetails::default(),
                 };
 
-                let _ = db.0.insert_event(&event);
+                event.id = db.insert_event(&event).ok().map(|inserted| inserted.id());
                 let _ = event_tx.send(event);
 
                 // Send desktop notification
-                send_notification(app, "✅ Feature Completed", &feature.description);
+                sink.notify("✅ Feature Completed", &feature.description);
             }
         }
     }
 
     // Sync features to database (preserving status from hooks)
-    let _ = db.0.sync_features(&project_dir, parsed_features);
+    let _ = db.sync_features(&project_dir, parsed_features);
 
     // Emit refresh event to frontend
-    let _ = app.emit("features-updated", &project_dir);
-}
-
-fn send_notification(app: &tauri::AppHandle, title: &str, body: &str) {
-    use tauri_plugin_notification::NotificationExt;
-    let _ = app.notification().builder().title(title).body(body).show();
+    sink.emit("features-updated", serde_json::json!(project_dir));
 }
@@ -0,0 +1,139 @@
+//! Ingestion latency benchmark
+//!
+//! Measures `insert_event` latency on its own and while several threads run
+//! large `get_events` queries, the load that used to block ingestion behind a
+//! single connection mutex. Run with output to see the numbers:
+//!
+//!   cargo test --release --test ingest_latency -- --nocapture
+
+use ijoka_core::db::{AgentEvent, Database, EventDetails};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const SEED_EVENTS: usize = 20_000;
+const MEASURED_INSERTS: usize = 300;
+const READER_THREADS: usize = 4;
+const READ_LIMIT: i64 = 5_000;
+
+fn temp_db_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ijoka-bench-{}-{}.db", name, std::process::id()));
+    remove_db(&path);
+    path
+}
+
+fn remove_db(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+fn event(n: usize) -> AgentEvent {
+    AgentEvent {
+        id: None,
+        event_type: "PostToolUse".to_string(),
+        source_agent: "claude-code".to_string(),
+        session_id: format!("session-{}", n % 50),
+        project_dir: "/tmp/bench-project".to_string(),
+        tool_name: Some("Bash".to_string()),
+        payload: Some(format!(r#"{{"command":"cargo test","n":{}}}"#, n)),
+        feature_id: None,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        idempotency_key: Some(format!("bench-{}", n)),
+        details: EventDetails::default(),
+    }
+}
+
+struct Latencies(Vec<Duration>);
+
+impl Latencies {
+    fn percentile(&self, p: f64) -> Duration {
+        let mut sorted = self.0.clone();
+        sorted.sort();
+        let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+        sorted[index]
+    }
+
+    fn report(&self, label: &str) {
+        println!(
+            "{:<24} n={:<5} p50={:>9.3?} p95={:>9.3?} p99={:>9.3?} max={:>9.3?}",
+            label,
+            self.0.len(),
+            self.percentile(0.50),
+            self.percentile(0.95),
+            self.percentile(0.99),
+            self.percentile(1.0),
+        );
+    }
+}
+
+fn measure_inserts(db: &Database, first: usize) -> Latencies {
+    Latencies(
+        (first..first + MEASURED_INSERTS)
+            .map(|n| {
+                let event = event(n);
+                let start = Instant::now();
+                db.insert_event(&event).expect("insert failed");
+                start.elapsed()
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn ingestion_latency_under_read_load() {
+    let path = temp_db_path("ingest");
+    let db = Arc::new(Database::new(&path).unwrap());
+
+    let seed: Vec<AgentEvent> = (0..SEED_EVENTS).map(event).collect();
+    db.insert_events(&seed).unwrap();
+
+    let idle = measure_inserts(&db, SEED_EVENTS);
+
+    // Readers hammer the events table until the measurement is done
+    let stop = Arc::new(AtomicBool::new(false));
+    let read_times = Arc::new(Mutex::new(Vec::new()));
+    let readers: Vec<_> = (0..READER_THREADS)
+        .map(|_| {
+            let (db, stop, read_times) = (Arc::clone(&db), Arc::clone(&stop), Arc::clone(&read_times));
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let start = Instant::now();
+                    let events = db.get_events(READ_LIMIT).expect("read failed");
+                    assert_eq!(events.len() as i64, READ_LIMIT);
+                    read_times.lock().unwrap().push(start.elapsed());
+                }
+            })
+        })
+        .collect();
+
+    // Let every reader get a query in flight
+    while read_times.lock().unwrap().len() < READER_THREADS {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let loaded = measure_inserts(&db, SEED_EVENTS + MEASURED_INSERTS);
+
+    stop.store(true, Ordering::Relaxed);
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    let reads = Latencies(read_times.lock().unwrap().clone());
+
+    idle.report("insert (idle)");
+    loaded.report("insert (under reads)");
+    reads.report("get_events(5000)");
+
+    // Writes no longer queue behind reads: a typical insert under load
+    // finishes well within the time of a single large read
+    assert!(
+        loaded.percentile(0.50) < reads.percentile(0.50),
+        "insert p50 {:?} not below read p50 {:?}",
+        loaded.percentile(0.50),
+        reads.percentile(0.50)
+    );
+
+    drop(db);
+    remove_db(&path);
+}